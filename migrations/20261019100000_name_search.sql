@@ -0,0 +1,30 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- confusable skeletons are computed by the bot when inserting, older rows are backfilled
+-- with the `backfill-name-skeletons` command.
+ALTER TABLE usernames ADD COLUMN skeleton TEXT;
+ALTER TABLE global_names ADD COLUMN skeleton TEXT;
+ALTER TABLE nicknames ADD COLUMN skeleton TEXT;
+
+CREATE INDEX usernames_lower_idx ON usernames (LOWER(username) text_pattern_ops);
+CREATE INDEX usernames_trgm_idx ON usernames USING GIN (LOWER(username) gin_trgm_ops);
+CREATE INDEX usernames_skeleton_idx ON usernames (skeleton);
+
+CREATE INDEX global_names_lower_idx ON global_names (LOWER(global_name) text_pattern_ops);
+CREATE INDEX global_names_trgm_idx ON global_names USING GIN (LOWER(global_name) gin_trgm_ops);
+CREATE INDEX global_names_skeleton_idx ON global_names (skeleton);
+
+CREATE INDEX nicknames_lower_idx ON nicknames (LOWER(nickname) text_pattern_ops);
+CREATE INDEX nicknames_trgm_idx ON nicknames USING GIN (LOWER(nickname) gin_trgm_ops);
+CREATE INDEX nicknames_skeleton_idx ON nicknames (skeleton);
+
+-- every name a user has held, filters on this view are pushed down to the indexes above.
+CREATE VIEW name_history AS
+    SELECT user_id, 'Username' AS kind, username AS name, NULL::BIGINT AS guild_id, skeleton, timestamp
+    FROM usernames
+    UNION ALL
+    SELECT user_id, 'Display name', global_name, NULL, skeleton, timestamp
+    FROM global_names
+    UNION ALL
+    SELECT user_id, 'Nickname', nickname, guild_id, skeleton, timestamp
+    FROM nicknames;
@@ -0,0 +1,4 @@
+-- used to find the latest name of users, both one at a time and during bulk syncs.
+CREATE INDEX usernames_user_latest_idx ON usernames (user_id, timestamp DESC);
+CREATE INDEX global_names_user_latest_idx ON global_names (user_id, timestamp DESC);
+CREATE INDEX nicknames_user_latest_idx ON nicknames (guild_id, user_id, timestamp DESC);
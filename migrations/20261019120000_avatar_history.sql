@@ -0,0 +1,26 @@
+CREATE TABLE avatars (
+    user_id BIGINT NOT NULL,
+    avatar_hash TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE TABLE guild_avatars (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    avatar_hash TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE TABLE banners (
+    user_id BIGINT NOT NULL,
+    banner_hash TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX avatars_user_latest_idx ON avatars (user_id, timestamp DESC);
+CREATE INDEX guild_avatars_user_latest_idx ON guild_avatars (guild_id, user_id, timestamp DESC);
+CREATE INDEX banners_user_latest_idx ON banners (user_id, timestamp DESC);
@@ -0,0 +1,12 @@
+CREATE TABLE role_changes (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    role_id BIGINT NOT NULL,
+    added BOOLEAN NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX role_changes_member_idx ON role_changes (guild_id, user_id, timestamp DESC);
@@ -0,0 +1,10 @@
+CREATE TABLE emote_renames (
+    id SERIAL PRIMARY KEY,
+    emote_id INT NOT NULL,
+    old_name TEXT NOT NULL,
+    new_name TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (emote_id) REFERENCES emotes(id)
+);
+
+CREATE INDEX emote_renames_emote_idx ON emote_renames (emote_id, timestamp DESC);
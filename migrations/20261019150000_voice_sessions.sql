@@ -0,0 +1,17 @@
+CREATE TABLE voice_sessions (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    -- the channel the user switched from, NULL if they joined voice directly.
+    switched_from BIGINT,
+    joined_at TIMESTAMP NOT NULL,
+    left_at TIMESTAMP,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX voice_sessions_member_idx ON voice_sessions (guild_id, user_id, joined_at DESC);
+CREATE INDEX voice_sessions_channel_idx ON voice_sessions (guild_id, channel_id);
+CREATE UNIQUE INDEX voice_sessions_open_idx ON voice_sessions (guild_id, user_id)
+    WHERE left_at IS NULL;
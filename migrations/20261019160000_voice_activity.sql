@@ -0,0 +1,17 @@
+CREATE TYPE VoiceActivityKind AS ENUM ('Stream', 'Video');
+
+CREATE TABLE voice_activity (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    kind VoiceActivityKind NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    ended_at TIMESTAMP,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX voice_activity_member_idx ON voice_activity (guild_id, user_id, started_at DESC);
+CREATE UNIQUE INDEX voice_activity_open_idx ON voice_activity (guild_id, user_id, kind)
+    WHERE ended_at IS NULL;
@@ -0,0 +1,16 @@
+CREATE TABLE vc_status_history (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    -- NULL until the change is attributed through the audit log.
+    user_id BIGINT,
+    old_status TEXT,
+    new_status TEXT,
+    blacklisted BOOLEAN NOT NULL DEFAULT FALSE,
+    cleared BOOLEAN NOT NULL DEFAULT FALSE,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX vc_status_history_channel_idx ON vc_status_history (channel_id, timestamp DESC);
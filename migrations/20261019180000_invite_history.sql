@@ -0,0 +1,23 @@
+CREATE TYPE MessageAction AS ENUM ('Allowed', 'Invalid', 'Log', 'Delete', 'Warn', 'DeleteAndTimeout');
+
+CREATE TABLE invite_posts (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    code TEXT NOT NULL,
+    -- NULL when the invite was expired or invalid.
+    target_guild_id BIGINT,
+    target_guild_name TEXT,
+    allowed BOOLEAN NOT NULL,
+    action MessageAction NOT NULL,
+    -- false when any part of the action failed.
+    succeeded BOOLEAN NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX invite_posts_guild_idx ON invite_posts (guild_id, target_guild_id);
+CREATE INDEX invite_posts_user_idx ON invite_posts (guild_id, user_id);
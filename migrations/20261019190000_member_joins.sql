@@ -0,0 +1,17 @@
+CREATE TYPE JoinMethod AS ENUM ('Invite', 'Vanity', 'Unknown');
+
+CREATE TABLE member_joins (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    method JoinMethod NOT NULL,
+    -- only set when the invite could be worked out.
+    code TEXT,
+    inviter_id BIGINT,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX member_joins_member_idx ON member_joins (guild_id, user_id, timestamp DESC);
+CREATE INDEX member_joins_inviter_idx ON member_joins (guild_id, inviter_id);
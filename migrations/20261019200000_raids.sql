@@ -0,0 +1,26 @@
+CREATE TABLE raids (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    ended_at TIMESTAMP,
+    -- only set when the verification level was raised in response.
+    previous_verification_level SMALLINT,
+    undone_at TIMESTAMP,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id)
+);
+
+CREATE TYPE SuspectAction AS ENUM ('None', 'TimedOut', 'TimeoutFailed', 'Kicked', 'KickFailed');
+
+CREATE TABLE raid_suspects (
+    raid_id INT NOT NULL,
+    user_id BIGINT NOT NULL,
+    score SMALLINT NOT NULL,
+    signals TEXT NOT NULL,
+    action SuspectAction NOT NULL,
+    joined_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (raid_id, user_id),
+    FOREIGN KEY (raid_id) REFERENCES raids(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX raids_guild_idx ON raids (guild_id, started_at DESC);
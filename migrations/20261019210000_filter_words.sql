@@ -0,0 +1,11 @@
+CREATE TYPE FilterList AS ENUM ('Bad', 'Fix');
+
+CREATE TABLE filter_words (
+    guild_id BIGINT NOT NULL,
+    list FilterList NOT NULL,
+    word TEXT NOT NULL,
+    added_by BIGINT,
+    added_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (guild_id, list, word),
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id)
+);
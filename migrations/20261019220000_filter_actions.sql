@@ -0,0 +1,19 @@
+CREATE TABLE filter_actions (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    content TEXT NOT NULL,
+    -- what rustrict rated the message as, if anything.
+    severity TEXT,
+    badlist_words TEXT[] NOT NULL,
+    action MessageAction NOT NULL,
+    -- false when any part of the action failed.
+    succeeded BOOLEAN NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (guild_id) REFERENCES guilds(guild_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX filter_actions_user_idx ON filter_actions (guild_id, user_id, timestamp DESC);
@@ -0,0 +1,3 @@
+-- when the bot last saw these open, so sessions left open by downtime end there instead of on restart.
+ALTER TABLE voice_sessions ADD COLUMN last_seen TIMESTAMP;
+ALTER TABLE voice_activity ADD COLUMN last_seen TIMESTAMP;
@@ -31,6 +31,7 @@ pub async fn dbstats(ctx: Context<'_>) -> Result<(), Error> {
         ("sticker_usage", "id"),
         ("emotes", "id"),
         ("emote_usage", "id"),
+        ("emote_renames", "id"),
     ];
     let misc_tables = [("dm_activity", "user_id"), ("starboard", "id")];
 
@@ -95,14 +96,19 @@ pub async fn insert_all_users(ctx: Context<'_>) -> Result<(), Error> {
         let guild = ctx.cache().guild(guild_id).unwrap();
         guild.members.clone()
     };
-    // this is not efficient in the slightest and locks the data several thousand times for massive guilds.
-    // I should really provide a better way of doing this in bulk.
-    for member in members {
-        ctx.data().check_or_insert_user(&member.user).await;
-        ctx.data()
-            .check_or_insert_nick(guild_id, member.user.id, member.nick.map(|s| s.to_string()))
-            .await;
-    }
+
+    let now = std::time::Instant::now();
+    let report = ctx.data().bulk_sync_names(guild_id, members.iter()).await?;
+
+    ctx.say(format!(
+        "Checked {} members in {}ms, inserted {} usernames, {} global names and {} nicknames.",
+        report.checked,
+        now.elapsed().as_millis(),
+        report.usernames,
+        report.global_names,
+        report.nicknames
+    ))
+    .await?;
 
     Ok(())
 }
@@ -161,7 +167,62 @@ pub async fn sql(
     Ok(())
 }
 
+/// Fills in the confusable skeletons of names recorded before they were tracked.
+#[poise::command(
+    rename = "backfill-name-skeletons",
+    prefix_command,
+    category = "Owner - Database",
+    owners_only,
+    hide_in_help
+)]
+pub async fn backfill_name_skeletons(ctx: Context<'_>) -> Result<(), Error> {
+    let db_pool = &ctx.data().database.db;
+    let now = std::time::Instant::now();
+
+    // the names tables have no primary key, so identical names are updated together.
+    let tables = [
+        ("usernames", "username"),
+        ("global_names", "global_name"),
+        ("nicknames", "nickname"),
+    ];
+
+    let mut summary = String::new();
+    for (table, column) in tables {
+        let names: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT DISTINCT {column} FROM {table} WHERE skeleton IS NULL"
+        ))
+        .fetch_all(db_pool)
+        .await?;
+
+        let skeletons = names
+            .iter()
+            .map(|n| moth_filter::skeleton(n))
+            .collect::<Vec<_>>();
+
+        let result = sqlx::query(&format!(
+            "UPDATE {table} t SET skeleton = v.skeleton FROM UNNEST($1::TEXT[], $2::TEXT[]) AS \
+             v(name, skeleton) WHERE t.{column} = v.name AND t.skeleton IS NULL"
+        ))
+        .bind(&names)
+        .bind(&skeletons)
+        .execute(db_pool)
+        .await?;
+
+        writeln!(summary, "**{table}**: {} rows", result.rows_affected()).unwrap();
+    }
+
+    write!(summary, "Finished in {}ms", now.elapsed().as_millis()).unwrap();
+    ctx.say(summary).await?;
+
+    Ok(())
+}
+
 #[must_use]
-pub fn commands() -> [crate::Command; 3] {
-    [dbstats(), insert_all_users(), sql()]
+pub fn commands() -> [crate::Command; 4] {
+    [
+        dbstats(),
+        insert_all_users(),
+        sql(),
+        backfill_name_skeletons(),
+    ]
 }
@@ -5,6 +5,7 @@ pub mod database;
 pub mod other;
 pub mod phil;
 pub mod presence;
+pub mod raids;
 
 use crate::{Context, Error};
 
@@ -21,6 +22,7 @@ pub fn commands() -> Vec<crate::Command> {
             .chain(other::commands())
             .chain(cooldowns::commands())
             .chain(phil::commands())
+            .chain(raids::commands())
             .collect()
     }
 }
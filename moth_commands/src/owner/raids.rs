@@ -0,0 +1,229 @@
+use crate::utils::paginate_lines;
+use crate::{owner::owner, Context, Error};
+use moth_data::database::SuspectAction;
+use poise::serenity_prelude::{EditGuild, EditMember, UserId, VerificationLevel};
+use sqlx::query;
+
+/// Review and undo the responses to raids.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "Owner - Raids",
+    check = "owner",
+    guild_only,
+    hide_in_help,
+    subcommands("list", "review", "undo"),
+    subcommand_required
+)]
+pub async fn raid(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List the raids detected in this server.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "Owner - Raids",
+    check = "owner",
+    guild_only,
+    hide_in_help
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let records = query!(
+        r#"SELECT r.id, r.started_at, r.ended_at, r.undone_at,
+             COUNT(s.user_id) AS "suspects!"
+           FROM raids r LEFT JOIN raid_suspects s ON s.raid_id = r.id
+           WHERE r.guild_id = $1
+           GROUP BY r.id ORDER BY r.started_at DESC"#,
+        ctx.guild_id().unwrap().get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    if records.is_empty() {
+        ctx.say("No raids have been detected here.").await?;
+        return Ok(());
+    }
+
+    let lines = records
+        .iter()
+        .map(|r| {
+            let started = r.started_at.and_utc().timestamp();
+            let status = match (r.ended_at, r.undone_at) {
+                (_, Some(_)) => "undone",
+                (Some(_), None) => "ended",
+                (None, None) => "ongoing",
+            };
+            format!(
+                "**#{}** <t:{started}:f>: {} suspects, {status}",
+                r.id, r.suspects
+            )
+        })
+        .collect::<Vec<_>>();
+
+    paginate_lines(ctx, "Raids", &lines, None).await?;
+
+    Ok(())
+}
+
+/// Show the suspects of a raid and what was done to them.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "Owner - Raids",
+    check = "owner",
+    guild_only,
+    hide_in_help
+)]
+pub async fn review(
+    ctx: Context<'_>,
+    #[description = "The raid number"] id: i32,
+) -> Result<(), Error> {
+    let records = query!(
+        r#"SELECT s.user_id, s.score, s.signals, s.action AS "action: SuspectAction", s.joined_at
+           FROM raid_suspects s JOIN raids r ON r.id = s.raid_id
+           WHERE s.raid_id = $1 AND r.guild_id = $2 ORDER BY s.joined_at"#,
+        id,
+        ctx.guild_id().unwrap().get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    if records.is_empty() {
+        ctx.say("I have no suspects recorded for that raid here.")
+            .await?;
+        return Ok(());
+    }
+
+    let lines = records
+        .iter()
+        .map(|r| {
+            let joined = r.joined_at.and_utc().timestamp();
+            let signals = if r.signals.is_empty() {
+                "no signals"
+            } else {
+                &r.signals
+            };
+            format!(
+                "<@{}> <t:{joined}:T>: score {}, {signals} ({})",
+                r.user_id,
+                r.score,
+                r.action.describe()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    paginate_lines(ctx, &format!("Raid #{id}"), &lines, None).await?;
+
+    Ok(())
+}
+
+/// Undo the response to a raid, restoring the verification level and removing timeouts.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "Owner - Raids",
+    check = "owner",
+    guild_only,
+    hide_in_help
+)]
+pub async fn undo(
+    ctx: Context<'_>,
+    #[description = "The raid number"] id: i32,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let db = &ctx.data().database.db;
+
+    let Some(raid) = query!(
+        "SELECT previous_verification_level, undone_at FROM raids WHERE id = $1 AND guild_id = $2",
+        id,
+        guild_id.get() as i64
+    )
+    .fetch_optional(db)
+    .await?
+    else {
+        ctx.say("There is no raid with that number here.").await?;
+        return Ok(());
+    };
+
+    if raid.undone_at.is_some() {
+        ctx.say("That raid was already undone.").await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let reason = format!("Undoing raid #{id}");
+    let mut summary = Vec::new();
+
+    if let Some(level) = raid.previous_verification_level {
+        let level = VerificationLevel::from(level as u8);
+        let builder = EditGuild::new()
+            .verification_level(level)
+            .audit_log_reason(&reason);
+        match guild_id.edit(ctx.http(), builder).await {
+            Ok(_) => summary.push(String::from("Restored the verification level.")),
+            Err(e) => summary.push(format!("Failed to restore the verification level: {e}")),
+        }
+    }
+
+    let suspects = query!(
+        r#"SELECT user_id, action AS "action: SuspectAction" FROM raid_suspects
+           WHERE raid_id = $1"#,
+        id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut untimed = 0;
+    let mut failed = 0;
+    let mut kicked = 0;
+    for suspect in &suspects {
+        match suspect.action {
+            SuspectAction::TimedOut => {
+                let user_id = UserId::new(suspect.user_id as u64);
+                let builder = EditMember::new()
+                    .enable_communication()
+                    .audit_log_reason(&reason);
+                match guild_id.edit_member(ctx.http(), user_id, builder).await {
+                    Ok(_) => untimed += 1,
+                    Err(_) => failed += 1,
+                }
+            }
+            SuspectAction::Kicked => kicked += 1,
+            _ => {}
+        }
+    }
+
+    if untimed != 0 || failed != 0 {
+        summary.push(format!(
+            "Removed {untimed} timeouts, {failed} could not be removed."
+        ));
+    }
+    if kicked != 0 {
+        summary.push(format!(
+            "{kicked} members were kicked and have to rejoin themselves."
+        ));
+    }
+
+    query!(
+        "UPDATE raids SET undone_at = $1 WHERE id = $2",
+        chrono::Utc::now().naive_utc(),
+        id
+    )
+    .execute(db)
+    .await?;
+
+    if summary.is_empty() {
+        summary.push(String::from("Nothing was done in response to this raid."));
+    }
+
+    ctx.say(summary.join("\n")).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [raid()]
+}
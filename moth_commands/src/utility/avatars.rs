@@ -0,0 +1,93 @@
+use crate::utils::{author_permissions, paginate_embeds};
+use crate::{Context, Error};
+use moth_data::avatars::ImageKind;
+use poise::serenity_prelude::{self as serenity, GuildId, User};
+use sqlx::query;
+
+/// Page through the past avatars and banners of a user.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    aliases("avatar-history", "pfps"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    user_cooldown = 5
+)]
+pub async fn avatars(
+    ctx: Context<'_>,
+    #[description = "The user to check the avatars of"] user: User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let records = query!(
+        r#"SELECT kind AS "kind!", hash AS "hash!", guild_id, timestamp AS "timestamp!" FROM (
+             SELECT 'Avatar' AS kind, avatar_hash AS hash, NULL::BIGINT AS guild_id, timestamp
+             FROM avatars WHERE user_id = $1
+             UNION ALL
+             SELECT 'Banner', banner_hash, NULL, timestamp
+             FROM banners WHERE user_id = $1
+             UNION ALL
+             SELECT 'Server avatar', avatar_hash, guild_id, timestamp
+             FROM guild_avatars WHERE user_id = $1
+           ) AS history ORDER BY timestamp DESC"#,
+        user.id.get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    // server avatars from other guilds are only shown to moderators.
+    let see_all_guilds = author_permissions(ctx).await?.manage_messages();
+
+    let records = records
+        .into_iter()
+        .filter(|r| see_all_guilds || r.guild_id.is_none_or(|id| id as u64 == guild_id.get()))
+        .collect::<Vec<_>>();
+
+    if records.is_empty() {
+        ctx.say("I have no avatar history for this user.").await?;
+        return Ok(());
+    }
+
+    let total = records.len();
+    let embeds = records
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            let kind = match (r.kind.as_str(), r.guild_id) {
+                ("Banner", _) => ImageKind::Banner,
+                (_, Some(id)) => ImageKind::GuildAvatar(GuildId::new(id as u64)),
+                _ => ImageKind::Avatar,
+            };
+
+            let url = kind.cdn_url(user.id, &r.hash);
+            let timestamp = r.timestamp.and_utc().timestamp();
+
+            let mut description = format!("Set <t:{timestamp}:F> (<t:{timestamp}:R>)");
+            if let ImageKind::GuildAvatar(id) = kind {
+                let name = id.name(ctx.cache()).unwrap_or_else(|| id.to_string());
+                description.push_str(&format!("\nin {name}"));
+            }
+
+            serenity::CreateEmbed::default()
+                .title(format!("{} of {}", r.kind, user.tag()))
+                .url(url.clone())
+                .description(description)
+                .image(url)
+                .footer(serenity::CreateEmbedFooter::new(format!(
+                    "{}/{total}",
+                    index + 1
+                )))
+        })
+        .collect::<Vec<_>>();
+
+    paginate_embeds(ctx, &embeds).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [avatars()]
+}
@@ -0,0 +1,246 @@
+use std::fmt::Write;
+
+use crate::utils::{
+    paginate_lines, parse_duration, snowflake_from_unix, unix_from_snowflake, unix_since,
+};
+use crate::{Context, Error};
+use chrono::DateTime;
+use moth_data::database::EmoteUsageType;
+use poise::serenity_prelude as serenity;
+use sqlx::query;
+
+use super::last_reactions_paginator::get_emoji_markdown;
+
+/// The window used when no duration is given.
+const DEFAULT_WINDOW: &str = "30d";
+
+#[derive(Debug, poise::ChoiceParameter, PartialEq)]
+pub enum AuditKind {
+    All,
+    Emotes,
+    Stickers,
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum AuditSort {
+    Usage,
+    #[name = "Last used"]
+    LastUsed,
+    Name,
+}
+
+struct AuditEntry {
+    kind: &'static str,
+    id: u64,
+    name: String,
+    display: String,
+    uses: i64,
+    last_used: Option<i64>,
+}
+
+/// Lists emotes and stickers in this server that are rarely or never used.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "expression-audit",
+    aliases("unused-emotes", "unused-stickers"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn expression_audit(
+    ctx: Context<'_>,
+    #[description = "Which expressions to check"] kind: Option<AuditKind>,
+    #[description = "How far back to look, e.g. 30d or 3mo (default 30d)"] duration: Option<String>,
+    #[description = "Include expressions used at most this many times (default 0)"]
+    #[min = 0]
+    max_uses: Option<i64>,
+    #[description = "How to sort the results"] sort: Option<AuditSort>,
+    #[description = "Attach the list as a CSV file"] csv: Option<bool>,
+) -> Result<(), Error> {
+    let duration = duration.unwrap_or_else(|| DEFAULT_WINDOW.to_string());
+    let Some(parsed) = parse_duration(&duration) else {
+        ctx.say("I could not parse that duration, try something like `7d`, `2w` or `3mo`.")
+            .await?;
+        return Ok(());
+    };
+
+    let kind = kind.unwrap_or(AuditKind::All);
+    let max_uses = max_uses.unwrap_or(0);
+    let since = unix_since(Some(parsed));
+    let guild_id = ctx.guild_id().unwrap();
+
+    let (emotes, stickers) = {
+        let Some(guild) = ctx.guild() else {
+            return Err("Could not retrieve guild from cache.".into());
+        };
+
+        let emotes = if kind == AuditKind::Stickers {
+            Vec::new()
+        } else {
+            guild
+                .emojis
+                .iter()
+                .map(|e| {
+                    (
+                        e.id.get(),
+                        e.name.to_string(),
+                        get_emoji_markdown(e).to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let stickers = if kind == AuditKind::Emotes {
+            Vec::new()
+        } else {
+            guild
+                .stickers
+                .iter()
+                .map(|s| (s.id.get(), s.name.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        (emotes, stickers)
+    };
+
+    let db = &ctx.data().database.db;
+    let mut entries = Vec::with_capacity(emotes.len() + stickers.len());
+
+    if !emotes.is_empty() {
+        let ids = emotes.iter().map(|e| e.0 as i64).collect::<Vec<_>>();
+        let types = [EmoteUsageType::Message, EmoteUsageType::ReactionAdd];
+
+        let records = query!(
+            "SELECT e.discord_id, COUNT(eu.id) FILTER (WHERE eu.used_at >= $3) AS uses, \
+             MAX(eu.used_at) AS last_used FROM emotes e JOIN emote_usage eu ON eu.emote_id = e.id \
+             WHERE e.discord_id = ANY($1) AND eu.guild_id = $2 AND eu.usage_type = ANY($4) GROUP \
+             BY e.discord_id",
+            &ids,
+            guild_id.get() as i64,
+            since,
+            &types as &[EmoteUsageType]
+        )
+        .fetch_all(db)
+        .await?;
+
+        for (id, name, display) in emotes {
+            let record = records.iter().find(|r| r.discord_id == Some(id as i64));
+
+            entries.push(AuditEntry {
+                kind: "emote",
+                id,
+                name,
+                display,
+                uses: record.and_then(|r| r.uses).unwrap_or(0),
+                last_used: record.and_then(|r| r.last_used),
+            });
+        }
+    }
+
+    if !stickers.is_empty() {
+        let ids = stickers.iter().map(|s| s.0 as i64).collect::<Vec<_>>();
+
+        let records = query!(
+            "SELECT sticker_id, COUNT(id) FILTER (WHERE message_id >= $3) AS uses, \
+             MAX(message_id) AS last_message FROM sticker_usage WHERE sticker_id = ANY($1) AND \
+             guild_id = $2 GROUP BY sticker_id",
+            &ids,
+            guild_id.get() as i64,
+            snowflake_from_unix(since)
+        )
+        .fetch_all(db)
+        .await?;
+
+        for (id, name) in stickers {
+            let record = records.iter().find(|r| r.sticker_id == id as i64);
+
+            entries.push(AuditEntry {
+                kind: "sticker",
+                id,
+                display: name.clone(),
+                name,
+                uses: record.and_then(|r| r.uses).unwrap_or(0),
+                last_used: record.and_then(|r| r.last_message).map(unix_from_snowflake),
+            });
+        }
+    }
+
+    entries.retain(|e| e.uses <= max_uses);
+
+    match sort.unwrap_or(AuditSort::Usage) {
+        AuditSort::Usage => entries.sort_by(|a, b| {
+            a.uses
+                .cmp(&b.uses)
+                .then_with(|| a.last_used.cmp(&b.last_used))
+        }),
+        // never used sorts first as None is less than Some.
+        AuditSort::LastUsed => entries.sort_by(|a, b| a.last_used.cmp(&b.last_used)),
+        AuditSort::Name => entries.sort_by_key(|e| e.name.to_lowercase()),
+    }
+
+    if entries.is_empty() {
+        ctx.say(format!(
+            "Every expression has been used more than {max_uses} times in the last {duration}."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    if csv.unwrap_or(false) {
+        let mut file = String::from("type,id,name,uses,last_used\n");
+        for entry in &entries {
+            let last_used = entry
+                .last_used
+                .and_then(|t| DateTime::from_timestamp(t, 0))
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default();
+
+            writeln!(
+                file,
+                "{},{},\"{}\",{},{last_used}",
+                entry.kind,
+                entry.id,
+                entry.name.replace('"', "\"\""),
+                entry.uses
+            )
+            .unwrap();
+        }
+
+        ctx.send(
+            poise::CreateReply::new()
+                .content(format!(
+                    "{} expressions used at most {max_uses} times in the last {duration}.",
+                    entries.len()
+                ))
+                .attachment(serenity::CreateAttachment::bytes(
+                    file.into_bytes(),
+                    "expression_audit.csv",
+                )),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let lines = entries
+        .iter()
+        .map(|e| {
+            let last_used = e
+                .last_used
+                .map_or_else(|| String::from("never"), |t| format!("<t:{t}:R>"));
+
+            format!(
+                "{} ({}): {} uses, last used {last_used}",
+                e.display, e.kind, e.uses
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let title = format!("Expressions used at most {max_uses} times (last {duration})");
+    paginate_lines(ctx, &title, &lines, Some(&format!("{} total", lines.len()))).await?;
+
+    Ok(())
+}
@@ -69,7 +69,7 @@ fn generate_embed<'a>(
     embed
 }
 
-fn get_emoji_markdown(emoji: &serenity::all::Emoji) -> ArrayString<57> {
+pub(super) fn get_emoji_markdown(emoji: &serenity::all::Emoji) -> ArrayString<57> {
     let mut buf = ArrayString::<57>::new();
 
     if emoji.animated() {
@@ -0,0 +1,369 @@
+use std::fmt::Write;
+
+use crate::utils::{paginate_lines, parse_duration, snowflake_from_unix, unix_since};
+use crate::{Context, Error};
+use moth_data::database::EmoteUsageType;
+use poise::serenity_prelude::{EmojiId, StickerId};
+use sqlx::query_as;
+
+use super::last_reactions_paginator::get_emoji_markdown;
+
+struct EmoteLeaderboardEntry {
+    emote_name: String,
+    discord_id: Option<i64>,
+    count: Option<i64>,
+}
+
+struct StickerLeaderboardEntry {
+    sticker_id: i64,
+    sticker_name: String,
+    count: Option<i64>,
+}
+
+struct StickerUserEntry {
+    user_id: i64,
+    count: Option<i64>,
+}
+
+/// Display the most used emotes in this server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "emote-leaderboard",
+    aliases("emoji-leaderboard", "emote-lb"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    subcommands("reactions", "messages", "all"),
+    subcommand_required
+)]
+pub async fn emote_leaderboard(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Display the most used emotes in reactions.
+#[poise::command(slash_command, prefix_command, category = "Utility", guild_only)]
+pub async fn reactions(
+    ctx: Context<'_>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+    #[description = "Only show emotes currently in this server"] in_guild: Option<bool>,
+) -> Result<(), Error> {
+    let types = [EmoteUsageType::ReactionAdd];
+    emote_shared(ctx, duration, in_guild, &types, "reactions").await
+}
+
+/// Display the most used emotes in messages.
+#[poise::command(slash_command, prefix_command, category = "Utility", guild_only)]
+pub async fn messages(
+    ctx: Context<'_>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+    #[description = "Only show emotes currently in this server"] in_guild: Option<bool>,
+) -> Result<(), Error> {
+    let types = [EmoteUsageType::Message];
+    emote_shared(ctx, duration, in_guild, &types, "messages").await
+}
+
+/// Display the most used emotes everywhere.
+#[poise::command(slash_command, prefix_command, category = "Utility", guild_only)]
+pub async fn all(
+    ctx: Context<'_>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+    #[description = "Only show emotes currently in this server"] in_guild: Option<bool>,
+) -> Result<(), Error> {
+    let types = [EmoteUsageType::ReactionAdd, EmoteUsageType::Message];
+    emote_shared(ctx, duration, in_guild, &types, "reactions and messages").await
+}
+
+/// Parses the optional duration argument, telling the user if it failed.
+///
+/// `Ok(None)` means the duration was invalid and the command should stop.
+async fn maybe_duration(
+    ctx: Context<'_>,
+    duration: Option<&str>,
+) -> Result<Option<Option<std::time::Duration>>, Error> {
+    let Some(duration) = duration else {
+        return Ok(Some(None));
+    };
+
+    if let Some(parsed) = parse_duration(duration) {
+        Ok(Some(Some(parsed)))
+    } else {
+        ctx.say("I could not parse that duration, try something like `7d`, `2w` or `3mo`.")
+            .await?;
+        Ok(None)
+    }
+}
+
+fn title_suffix(duration: Option<&str>) -> String {
+    duration.map_or_else(String::new, |d| format!(" (last {d})"))
+}
+
+async fn emote_shared(
+    ctx: Context<'_>,
+    duration: Option<String>,
+    in_guild: Option<bool>,
+    types: &[EmoteUsageType],
+    kind: &str,
+) -> Result<(), Error> {
+    let Some(parsed) = maybe_duration(ctx, duration.as_deref()).await? else {
+        return Ok(());
+    };
+
+    let guild_id = ctx.guild_id().unwrap();
+    let since = unix_since(parsed);
+
+    let records = query_as!(
+        EmoteLeaderboardEntry,
+        "SELECT e.emote_name, e.discord_id, COUNT(eu.id) AS count FROM emote_usage eu JOIN \
+         emotes e ON eu.emote_id = e.id WHERE eu.guild_id = $1 AND eu.usage_type = ANY($2) AND \
+         eu.used_at >= $3 GROUP BY e.id, e.emote_name, e.discord_id ORDER BY count DESC",
+        guild_id.get() as i64,
+        types as &[EmoteUsageType],
+        since
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    let lines = {
+        let Some(guild) = ctx.guild() else {
+            return Err("Could not retrieve guild from cache.".into());
+        };
+
+        let mut lines = Vec::new();
+        for record in records {
+            let emoji = record
+                .discord_id
+                .and_then(|id| guild.emojis.get(&EmojiId::new(id as u64)));
+
+            // standard emojis have no id, custom emotes from elsewhere have one.
+            if in_guild.unwrap_or(false) && emoji.is_none() {
+                continue;
+            }
+
+            let name = emoji.map_or_else(
+                || record.emote_name.clone(),
+                |e| get_emoji_markdown(e).to_string(),
+            );
+
+            let mut line = String::new();
+            write!(
+                line,
+                "**{}.** {name}: {}",
+                lines.len() + 1,
+                record.count.unwrap_or(0)
+            )
+            .unwrap();
+            lines.push(line);
+        }
+
+        lines
+    };
+
+    if lines.is_empty() {
+        ctx.say("No expressions").await?;
+        return Ok(());
+    }
+
+    let title = format!("Top emotes in {kind}{}", title_suffix(duration.as_deref()));
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+/// Display the users of a sticker, or the top sticker users if none is given.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "sticker-usage",
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild"
+)]
+pub async fn sticker_usage(
+    ctx: Context<'_>,
+    #[description = "The sticker name or ID"] sticker: Option<String>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+) -> Result<(), Error> {
+    let Some(parsed) = maybe_duration(ctx, duration.as_deref()).await? else {
+        return Ok(());
+    };
+
+    let guild_id = ctx.guild_id().unwrap();
+    let min_message = snowflake_from_unix(unix_since(parsed));
+    let database = &ctx.data().database;
+
+    let (title, records) = if let Some(sticker) = sticker {
+        let Some((sticker_id, name)) = resolve_sticker(ctx, &sticker).await? else {
+            ctx.say("I could not find a sticker by that name or ID.")
+                .await?;
+            return Ok(());
+        };
+
+        let records = query_as!(
+            StickerUserEntry,
+            "SELECT user_id, COUNT(id) AS count FROM sticker_usage WHERE guild_id = $1 AND \
+             sticker_id = $2 AND message_id >= $3 GROUP BY user_id ORDER BY count DESC",
+            guild_id.get() as i64,
+            sticker_id.get() as i64,
+            min_message
+        )
+        .fetch_all(&database.db)
+        .await?;
+
+        (format!("Top {name} users"), records)
+    } else {
+        let records = query_as!(
+            StickerUserEntry,
+            "SELECT user_id, COUNT(id) AS count FROM sticker_usage WHERE guild_id = $1 AND \
+             message_id >= $2 GROUP BY user_id ORDER BY count DESC",
+            guild_id.get() as i64,
+            min_message
+        )
+        .fetch_all(&database.db)
+        .await?;
+
+        (String::from("Top sticker users"), records)
+    };
+
+    let lines = records
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            format!(
+                "**{}.** <@{}>: {}",
+                index + 1,
+                r.user_id as u64,
+                r.count.unwrap_or(0)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        ctx.say("No stickers").await?;
+        return Ok(());
+    }
+
+    let title = format!("{title}{}", title_suffix(duration.as_deref()));
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+/// Display the most used stickers in this server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "sticker-leaderboard",
+    aliases("sticker-lb"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild"
+)]
+pub async fn sticker_leaderboard(
+    ctx: Context<'_>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+    #[description = "Only show stickers currently in this server"] in_guild: Option<bool>,
+) -> Result<(), Error> {
+    let Some(parsed) = maybe_duration(ctx, duration.as_deref()).await? else {
+        return Ok(());
+    };
+
+    let guild_id = ctx.guild_id().unwrap();
+    let min_message = snowflake_from_unix(unix_since(parsed));
+
+    let records = query_as!(
+        StickerLeaderboardEntry,
+        "SELECT s.sticker_id, s.sticker_name, COUNT(su.id) AS count FROM sticker_usage su JOIN \
+         stickers s ON su.sticker_id = s.sticker_id WHERE su.guild_id = $1 AND su.message_id >= \
+         $2 GROUP BY s.sticker_id, s.sticker_name ORDER BY count DESC",
+        guild_id.get() as i64,
+        min_message
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    let lines = {
+        let Some(guild) = ctx.guild() else {
+            return Err("Could not retrieve guild from cache.".into());
+        };
+
+        records
+            .iter()
+            .filter(|r| {
+                !in_guild.unwrap_or(false)
+                    || guild
+                        .stickers
+                        .get(&StickerId::new(r.sticker_id as u64))
+                        .is_some()
+            })
+            .enumerate()
+            .map(|(index, r)| {
+                format!(
+                    "**{}.** {}: {}",
+                    index + 1,
+                    r.sticker_name,
+                    r.count.unwrap_or(0)
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if lines.is_empty() {
+        ctx.say("No stickers").await?;
+        return Ok(());
+    }
+
+    let title = format!("Top stickers{}", title_suffix(duration.as_deref()));
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+/// Resolves a sticker by ID or name, checking the guild before the database.
+async fn resolve_sticker(
+    ctx: Context<'_>,
+    sticker: &str,
+) -> Result<Option<(StickerId, String)>, Error> {
+    let sticker = sticker.trim();
+
+    if let Ok(id) = sticker.parse::<u64>() {
+        let name = sqlx::query!(
+            "SELECT sticker_name FROM stickers WHERE sticker_id = $1",
+            id as i64
+        )
+        .fetch_optional(&ctx.data().database.db)
+        .await?
+        .map_or_else(|| id.to_string(), |r| r.sticker_name);
+
+        return Ok(Some((StickerId::new(id), name)));
+    }
+
+    let cached = {
+        let Some(guild) = ctx.guild() else {
+            return Err("Could not retrieve guild from cache.".into());
+        };
+
+        guild
+            .stickers
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(sticker))
+            .map(|s| (s.id, s.name.to_string()))
+    };
+
+    if cached.is_some() {
+        return Ok(cached);
+    }
+
+    let record = sqlx::query!(
+        "SELECT sticker_id, sticker_name FROM stickers WHERE LOWER(sticker_name) = LOWER($1) \
+         LIMIT 1",
+        sticker
+    )
+    .fetch_optional(&ctx.data().database.db)
+    .await?;
+
+    Ok(record.map(|r| (StickerId::new(r.sticker_id as u64), r.sticker_name)))
+}
@@ -1,13 +1,17 @@
 use crate::{Context, Error};
 use std::{borrow::Cow, fmt};
+mod audit;
 mod last_reactions;
 // TODO: sorta combine with the one in utils
 mod last_reactions_paginator;
+mod leaderboard;
+mod profile;
 mod query;
 mod utils;
 
 use moth_data::database::EmoteUsageType;
 use moth_events::handlers::messages::EMOJI_REGEX;
+use poise::serenity_prelude as serenity;
 use query::handle_expression_query;
 
 use utils::{check_in_guild, display_expressions};
@@ -44,7 +48,7 @@ struct ExpressionCounts {
     guild_only,
     install_context = "Guild",
     interaction_context = "Guild",
-    subcommands("reactions", "messages", "all"),
+    subcommands("reactions", "messages", "all", "user"),
     subcommand_required
 )]
 pub async fn emoji_usage(_: Context<'_>) -> Result<(), Error> {
@@ -95,6 +99,12 @@ pub async fn all(ctx: Context<'_>, emoji: String) -> Result<(), Error> {
     shared(ctx, emoji, &types, None).await
 }
 
+/// Display the emotes a user uses the most.
+#[poise::command(slash_command, prefix_command, category = "Utility", guild_only)]
+pub async fn user(ctx: Context<'_>, user: serenity::User) -> Result<(), Error> {
+    profile::user_profile(ctx, user).await
+}
+
 async fn shared(
     ctx: Context<'_>,
     emoji: String,
@@ -125,14 +135,14 @@ async fn shared(
     display_expressions(ctx, &results, &expression, in_guild, msg_type).await
 }
 
-// /emote-leaderboard reactions [duration]
-// /emote-leaderboard messages [duration]
-// /emote-leaderboard all [duration]
-
-// /sticker-usage [sticker]
-// /sticker-leaderboard [duration]
-
 #[must_use]
-pub fn commands() -> [crate::Command; 2] {
-    [emoji_usage(), last_reactions::last_reactions()]
+pub fn commands() -> [crate::Command; 6] {
+    [
+        emoji_usage(),
+        last_reactions::last_reactions(),
+        leaderboard::emote_leaderboard(),
+        leaderboard::sticker_usage(),
+        leaderboard::sticker_leaderboard(),
+        audit::expression_audit(),
+    ]
 }
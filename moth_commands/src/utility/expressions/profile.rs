@@ -0,0 +1,104 @@
+use crate::utils::{author_permissions, paginate_lines};
+use crate::{Context, Error};
+use chrono::Utc;
+use poise::serenity_prelude::{EmojiId, User};
+use sqlx::query;
+
+use super::last_reactions_paginator::get_emoji_markdown;
+
+/// The size of the window used to compare recent usage against.
+const TREND_WINDOW: i64 = 60 * 60 * 24 * 30;
+
+pub(super) async fn user_profile(ctx: Context<'_>, user: User) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let now = Utc::now().timestamp();
+    let recent = now - TREND_WINDOW;
+    let previous = recent - TREND_WINDOW;
+
+    let records = query!(
+        "SELECT e.emote_name, e.discord_id, COUNT(eu.id) FILTER (WHERE eu.usage_type = \
+         'Message') AS messages, COUNT(eu.id) FILTER (WHERE eu.usage_type = 'ReactionAdd') AS \
+         reactions, COUNT(eu.id) FILTER (WHERE eu.used_at >= $3) AS recent, COUNT(eu.id) FILTER \
+         (WHERE eu.used_at >= $4 AND eu.used_at < $3) AS previous, MIN(eu.used_at) AS \
+         first_used, MAX(eu.used_at) AS last_used FROM emote_usage eu JOIN emotes e ON \
+         eu.emote_id = e.id WHERE eu.user_id = $1 AND eu.guild_id = $2 AND eu.usage_type != \
+         'ReactionRemove' GROUP BY e.id, e.emote_name, e.discord_id ORDER BY COUNT(eu.id) DESC",
+        user.id.get() as i64,
+        guild_id.get() as i64,
+        recent,
+        previous
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    let can_see_all = author_permissions(ctx).await?.manage_messages();
+
+    let (lines, hidden) = {
+        let Some(guild) = ctx.guild() else {
+            return Err("Could not retrieve guild from cache.".into());
+        };
+
+        let mut hidden = 0_usize;
+        let mut lines = Vec::new();
+        for record in &records {
+            let emoji = record
+                .discord_id
+                .and_then(|id| guild.emojis.get(&EmojiId::new(id as u64)));
+
+            // standard emojis have no id and are always visible.
+            if record.discord_id.is_some() && emoji.is_none() && !can_see_all {
+                hidden += 1;
+                continue;
+            }
+
+            let name = emoji.map_or_else(
+                || record.emote_name.clone(),
+                |e| get_emoji_markdown(e).to_string(),
+            );
+
+            let messages = record.messages.unwrap_or(0);
+            let reactions = record.reactions.unwrap_or(0);
+            let trend = trend(record.recent.unwrap_or(0), record.previous.unwrap_or(0));
+
+            let mut line = format!(
+                "**{}.** {name}: {} ({messages} messages, {reactions} reactions) {trend}",
+                lines.len() + 1,
+                messages + reactions
+            );
+
+            if let (Some(first), Some(last)) = (record.first_used, record.last_used) {
+                line.push_str(&format!("\nfirst <t:{first}:d>, last <t:{last}:R>"));
+            }
+
+            lines.push(line);
+        }
+
+        (lines, hidden)
+    };
+
+    if lines.is_empty() {
+        ctx.say("No expressions").await?;
+        return Ok(());
+    }
+
+    let footer = if hidden == 0 {
+        String::from("Trend compares the last 30 days to the 30 before")
+    } else {
+        format!("{hidden} emotes from other servers hidden")
+    };
+
+    let title = format!("Emotes used by {}", user.tag());
+    paginate_lines(ctx, &title, &lines, Some(&footer)).await?;
+
+    Ok(())
+}
+
+fn trend(recent: i64, previous: i64) -> &'static str {
+    match (recent, previous) {
+        (0, 0) => "",
+        (_, 0) => "(new)",
+        (r, p) if r > p => "📈",
+        (r, p) if r < p => "📉",
+        _ => "➡️",
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::utils::paginate_lines;
+use crate::{Context, Error};
+use moth_config::{parse_words, read_words_from_file};
+use moth_data::database::FilterList;
+use poise::serenity_prelude::{Attachment, CreateAttachment};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum WordList {
+    #[name = "badlist"]
+    Badlist,
+    #[name = "fixlist"]
+    Fixlist,
+}
+
+impl WordList {
+    fn list(self) -> FilterList {
+        match self {
+            WordList::Badlist => FilterList::Bad,
+            WordList::Fixlist => FilterList::Fix,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            WordList::Badlist => "badlist",
+            WordList::Fixlist => "fixlist",
+        }
+    }
+
+    /// The file the list used to be loaded from, imported when no file is given.
+    fn default_file(self) -> &'static str {
+        match self {
+            WordList::Badlist => "config/lists/badwords.txt",
+            WordList::Fixlist => "config/lists/fixwords.txt",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            WordList::Badlist => "badwords.txt",
+            WordList::Fixlist => "fixwords.txt",
+        }
+    }
+}
+
+/// Returns the current words of one of this guilds lists.
+fn current_words(ctx: Context<'_>, list: WordList) -> HashSet<String> {
+    let config = ctx.data().config.read();
+    let Some(lists) = config.events.filter_lists.get(&ctx.guild_id().unwrap()) else {
+        return HashSet::new();
+    };
+
+    match list {
+        WordList::Badlist => lists.badlist.clone(),
+        WordList::Fixlist => lists.fixlist.clone(),
+    }
+}
+
+fn split_words(words: &str) -> Vec<String> {
+    let words = words
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<HashSet<_>>();
+
+    words.into_iter().collect()
+}
+
+async fn add_words(ctx: Context<'_>, list: WordList, words: &[String]) -> Result<u64, Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let data = ctx.data();
+
+    let added = data
+        .database
+        .add_filter_words(guild_id, list.list(), words, ctx.author().id)
+        .await?;
+    data.refresh_filter_lists(guild_id).await?;
+
+    Ok(added)
+}
+
+/// Manage the words the filter flags in this server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list", "import", "export"),
+    subcommand_required
+)]
+pub async fn filter(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Add words to one of the filter lists.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "The list to add to"] list: WordList,
+    #[description = "The words to add, separated by spaces"]
+    #[rest]
+    words: String,
+) -> Result<(), Error> {
+    let words = split_words(&words);
+
+    let added = add_words(ctx, list, &words).await?;
+    ctx.say(format!(
+        "Added {added} of {} words to the {}.",
+        words.len(),
+        list.name()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Remove words from one of the filter lists.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "The list to remove from"] list: WordList,
+    #[description = "The words to remove, separated by spaces"]
+    #[rest]
+    words: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let data = ctx.data();
+
+    let words = split_words(&words);
+
+    let removed = data
+        .database
+        .remove_filter_words(guild_id, list.list(), &words)
+        .await?;
+    data.refresh_filter_lists(guild_id).await?;
+
+    ctx.say(format!(
+        "Removed {removed} of {} words from the {}.",
+        words.len(),
+        list.name()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Show the words on one of the filter lists.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    ephemeral
+)]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "The list to show"] list: WordList,
+) -> Result<(), Error> {
+    let mut words = current_words(ctx, list).into_iter().collect::<Vec<_>>();
+
+    if words.is_empty() {
+        ctx.say(format!("The {} is empty.", list.name())).await?;
+        return Ok(());
+    }
+
+    words.sort_unstable();
+    let lines = words.iter().map(|w| format!("`{w}`")).collect::<Vec<_>>();
+    paginate_lines(ctx, list.name(), &lines, None).await?;
+
+    Ok(())
+}
+
+/// Import words into one of the filter lists from a file, one word per line.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "The list to import into"] list: WordList,
+    #[description = "The file to import, defaults to the bots own list"] file: Option<Attachment>,
+) -> Result<(), Error> {
+    let words = match file {
+        Some(file) => {
+            let bytes = file.download().await?;
+            let Ok(content) = String::from_utf8(bytes) else {
+                ctx.say("That file isn't valid text.").await?;
+                return Ok(());
+            };
+            parse_words(&content)
+        }
+        None => match read_words_from_file(list.default_file()) {
+            Ok(words) => words,
+            Err(e) => {
+                ctx.say(format!("Failed to read `{}`: {e}", list.default_file()))
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let words = words.into_iter().collect::<Vec<_>>();
+    let added = add_words(ctx, list, &words).await?;
+    ctx.say(format!(
+        "Imported {added} new words into the {}, {} were already on it.",
+        list.name(),
+        words.len() as u64 - added
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Export one of the filter lists as a file.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    ephemeral
+)]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "The list to export"] list: WordList,
+) -> Result<(), Error> {
+    let mut words = current_words(ctx, list).into_iter().collect::<Vec<_>>();
+    words.sort_unstable();
+
+    let attachment = CreateAttachment::bytes(words.join("\n").into_bytes(), list.file_name());
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("The {} has {} words.", list.name(), words.len()))
+            .attachment(attachment),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Show how the filter treats some text, step by step.
+#[poise::command(
+    rename = "filter-explain",
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "MANAGE_GUILD",
+    ephemeral
+)]
+pub async fn filter_explain(
+    ctx: Context<'_>,
+    #[description = "The text to explain"]
+    #[rest]
+    content: String,
+) -> Result<(), Error> {
+    let lists = ctx
+        .data()
+        .config
+        .read()
+        .events
+        .filter_lists_for(ctx.guild_id())
+        .clone();
+
+    let explanation = moth_filter::explain(&content, &lists);
+
+    let mut lines = Vec::with_capacity(explanation.tokens.len());
+    for token in &explanation.tokens {
+        let mut line = format!(
+            "{} `{}`\n-# visible `{}` → rustrict `{}` → normalised `{}`",
+            if token.flagged() { "🔴" } else { "⚪" },
+            token.token.words.join(" "),
+            token.token.visible,
+            token.token.plain,
+            token.token.normalised
+        );
+
+        if let Some(word) = &token.badlist {
+            write!(line, "\n**Badlist:** `{word}`").unwrap();
+        }
+        if let Some(word) = &token.fixlist {
+            write!(line, "\n**Fixlist:** `{word}`, cancels the badlist").unwrap();
+        }
+        if token.rustrict != moth_filter::Type::NONE {
+            write!(line, "\n**Rustrict:** `{:?}`", token.rustrict).unwrap();
+        }
+        if let Some((word, kind)) = token.trie_override {
+            write!(line, "\n**Override:** `{word}` is `{kind:?}`").unwrap();
+        }
+
+        lines.push(line);
+    }
+
+    let footer = match explanation.severity() {
+        Some(severity) => format!("Severity: {severity:?} ({:?})", explanation.kind),
+        None => format!("Not inappropriate ({:?})", explanation.kind),
+    };
+
+    paginate_lines(ctx, "Filter explanation", &lines, Some(&footer)).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [filter(), filter_explain()]
+}
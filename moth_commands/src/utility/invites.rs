@@ -0,0 +1,220 @@
+use crate::utils::{paginate_lines, parse_duration, unix_since};
+use crate::{Context, Error};
+use chrono::{DateTime, NaiveDateTime};
+use moth_data::database::JoinMethod;
+use poise::serenity_prelude::User;
+use sqlx::query;
+
+/// Parses the optional duration argument into the time to look back to.
+///
+/// `Ok(None)` means the duration was invalid and the user was told.
+async fn parse_since(
+    ctx: Context<'_>,
+    duration: Option<&str>,
+) -> Result<Option<NaiveDateTime>, Error> {
+    let since = match duration.map(parse_duration) {
+        Some(None) => {
+            ctx.say("I could not parse that duration, try something like `7d`, `2w` or `3mo`.")
+                .await?;
+            return Ok(None);
+        }
+        Some(parsed) => unix_since(parsed),
+        None => 0,
+    };
+
+    Ok(Some(
+        DateTime::from_timestamp(since, 0)
+            .unwrap_or_default()
+            .naive_utc(),
+    ))
+}
+
+/// Show the servers most advertised here.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "invite-stats",
+    aliases("advertised"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "MANAGE_MESSAGES",
+    user_cooldown = 5
+)]
+pub async fn invite_stats(
+    ctx: Context<'_>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+) -> Result<(), Error> {
+    let Some(since) = parse_since(ctx, duration.as_deref()).await? else {
+        return Ok(());
+    };
+
+    let records = query!(
+        r#"SELECT target_guild_id AS "target_guild_id!",
+             (ARRAY_AGG(target_guild_name ORDER BY timestamp DESC))[1] AS "name!",
+             COUNT(*) AS "posts!",
+             COUNT(DISTINCT user_id) AS "posters!",
+             COUNT(*) FILTER (WHERE NOT allowed) AS "disallowed!"
+           FROM invite_posts
+           WHERE guild_id = $1 AND target_guild_id IS NOT NULL
+             AND timestamp >= $2
+           GROUP BY target_guild_id ORDER BY "posts!" DESC"#,
+        ctx.guild_id().unwrap().get() as i64,
+        since
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    if records.is_empty() {
+        ctx.say("Nobody has posted an invite here yet.").await?;
+        return Ok(());
+    }
+
+    let lines = records
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            format!(
+                "{}. **{}** ({}): {} posts by {} users, {} disallowed",
+                index + 1,
+                r.name,
+                r.target_guild_id,
+                r.posts,
+                r.posters,
+                r.disallowed
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let title = duration.map_or_else(
+        || String::from("Most advertised servers"),
+        |d| format!("Most advertised servers (last {d})"),
+    );
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+/// Commands for the invites members joined with.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    subcommands("leaderboard"),
+    subcommand_required
+)]
+pub async fn invites(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show who invited the most members.
+#[poise::command(slash_command, prefix_command, category = "Utility", guild_only)]
+pub async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "How far back to look, e.g. 7d or 3mo"] duration: Option<String>,
+) -> Result<(), Error> {
+    let Some(since) = parse_since(ctx, duration.as_deref()).await? else {
+        return Ok(());
+    };
+
+    let records = query!(
+        r#"SELECT inviter_id AS "inviter_id!", COUNT(*) AS "joins!",
+             COUNT(DISTINCT code) AS "codes!"
+           FROM member_joins
+           WHERE guild_id = $1 AND inviter_id IS NOT NULL AND timestamp >= $2
+           GROUP BY inviter_id ORDER BY "joins!" DESC"#,
+        ctx.guild_id().unwrap().get() as i64,
+        since
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    if records.is_empty() {
+        ctx.say("I haven't seen anyone join through a tracked invite yet.")
+            .await?;
+        return Ok(());
+    }
+
+    let lines = records
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            format!(
+                "{}. <@{}>: {} joins over {} invites",
+                index + 1,
+                r.inviter_id,
+                r.joins,
+                r.codes
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let title = duration.map_or_else(
+        || String::from("Invite leaderboard"),
+        |d| format!("Invite leaderboard (last {d})"),
+    );
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+/// Show which invites a member joined with.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "joined-via",
+    aliases("joinedvia", "invited-by"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    user_cooldown = 5
+)]
+pub async fn joined_via(
+    ctx: Context<'_>,
+    #[description = "The member to check"] user: User,
+) -> Result<(), Error> {
+    let records = query!(
+        r#"SELECT method AS "method!: JoinMethod", code, inviter_id, timestamp FROM member_joins
+           WHERE guild_id = $1 AND user_id = $2 ORDER BY timestamp DESC"#,
+        ctx.guild_id().unwrap().get() as i64,
+        user.id.get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    if records.is_empty() {
+        ctx.say("I have no joins recorded for this user.").await?;
+        return Ok(());
+    }
+
+    let lines = records
+        .iter()
+        .map(|r| {
+            let timestamp = r.timestamp.and_utc().timestamp();
+            let via = match (r.method, &r.code, r.inviter_id) {
+                (JoinMethod::Invite, Some(code), Some(inviter)) => {
+                    format!("`{code}` by <@{inviter}>")
+                }
+                (JoinMethod::Invite, Some(code), None) => format!("`{code}`"),
+                (JoinMethod::Vanity, _, _) => String::from("the vanity url"),
+                _ => String::from("an unknown invite"),
+            };
+            format!("<t:{timestamp}:f>: joined via {via}")
+        })
+        .collect::<Vec<_>>();
+
+    let title = format!("Joins of {}", user.tag());
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 3] {
+    [invite_stats(), invites(), joined_via()]
+}
@@ -1,8 +1,15 @@
+pub mod avatars;
 pub mod charinfo;
 pub mod expressions;
+pub mod filter;
 pub mod guild;
+pub mod invites;
+pub mod names;
+pub mod nicknames;
 pub mod random;
 pub mod users;
+pub mod vc_status;
+pub mod voice;
 
 #[must_use]
 pub fn commands() -> Vec<crate::Command> {
@@ -13,6 +20,13 @@ pub fn commands() -> Vec<crate::Command> {
             .chain(users::commands())
             .chain(guild::commands())
             .chain(charinfo::commands())
+            .chain(names::commands())
+            .chain(nicknames::commands())
+            .chain(avatars::commands())
+            .chain(voice::commands())
+            .chain(vc_status::commands())
+            .chain(invites::commands())
+            .chain(filter::commands())
             .collect()
     }
 }
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+
+use crate::utils::{author_permissions, paginate_lines};
+use crate::{Context, Error};
+use chrono::NaiveDateTime;
+use poise::serenity_prelude::{GuildId, User};
+use sqlx::{query, query_as};
+
+/// The most matches a single search will return.
+const SEARCH_LIMIT: i64 = 500;
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum SearchMode {
+    Exact,
+    Prefix,
+    Fuzzy,
+    Confusable,
+}
+
+struct NameMatch {
+    user_id: i64,
+    kind: String,
+    name: String,
+    guild_id: Option<i64>,
+    first_held: NaiveDateTime,
+    last_held: NaiveDateTime,
+}
+
+/// Display a timeline of a user's usernames, display names and nicknames.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    aliases("name-history", "nicknames"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    user_cooldown = 5
+)]
+pub async fn names(
+    ctx: Context<'_>,
+    #[description = "The user to check the names of"] user: User,
+    #[description = "Only show nicknames from this server"] current_guild: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let records = query!(
+        r#"SELECT kind AS "kind!", name AS "name!", guild_id, timestamp AS "timestamp!"
+           FROM name_history WHERE user_id = $1 ORDER BY timestamp DESC"#,
+        user.id.get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    let visible_guilds = if current_guild.unwrap_or(false) {
+        Some(HashSet::from([guild_id]))
+    } else if author_permissions(ctx).await?.manage_messages() {
+        None
+    } else {
+        Some(shared_guilds(ctx, guild_id, &user))
+    };
+
+    let cache = ctx.cache();
+    let lines = records
+        .iter()
+        .filter_map(|r| {
+            let guild = match r.guild_id {
+                Some(id) => {
+                    let id = GuildId::new(id as u64);
+                    if visible_guilds.as_ref().is_some_and(|g| !g.contains(&id)) {
+                        return None;
+                    }
+
+                    let name = id.name(cache).unwrap_or_else(|| id.to_string());
+                    format!(" in {name}")
+                }
+                None => String::new(),
+            };
+
+            Some(format!(
+                "<t:{}:d> **{}**{guild}: {}",
+                r.timestamp.and_utc().timestamp(),
+                r.kind,
+                r.name
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        ctx.say("I have no name history for this user.").await?;
+        return Ok(());
+    }
+
+    let title = format!("Name history for {}", user.tag());
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+/// The guilds in cache that both the author and the target user are in.
+///
+/// The current guild is always included.
+fn shared_guilds(ctx: Context<'_>, current: GuildId, user: &User) -> HashSet<GuildId> {
+    let author_id = ctx.author().id;
+    let cache = ctx.cache();
+
+    let mut shared = HashSet::from([current]);
+    for guild_id in cache.guilds() {
+        let Some(guild) = cache.guild(guild_id) else {
+            continue;
+        };
+
+        if guild.members.contains_key(&author_id) && guild.members.contains_key(&user.id) {
+            shared.insert(guild_id);
+        }
+    }
+
+    shared
+}
+
+/// Find every user who has ever held a matching name.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "name-search",
+    aliases("search-names", "who-was"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn name_search(
+    ctx: Context<'_>,
+    #[description = "The name to search for"] name: String,
+    #[description = "How to match the name (default exact)"] mode: Option<SearchMode>,
+) -> Result<(), Error> {
+    let mode = mode.unwrap_or(SearchMode::Exact);
+    let db = &ctx.data().database.db;
+
+    let records = match mode {
+        SearchMode::Exact => {
+            query_as!(
+                NameMatch,
+                r#"SELECT user_id AS "user_id!", kind AS "kind!", name AS "name!", guild_id,
+                   MIN(timestamp) AS "first_held!", MAX(timestamp) AS "last_held!"
+                   FROM name_history WHERE LOWER(name) = LOWER($1)
+                   GROUP BY user_id, kind, name, guild_id ORDER BY MAX(timestamp) DESC LIMIT $2"#,
+                name,
+                SEARCH_LIMIT
+            )
+            .fetch_all(db)
+            .await?
+        }
+        SearchMode::Prefix => {
+            // LIKE wildcards in the name should be matched literally.
+            let escaped = name
+                .to_lowercase()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            let pattern = format!("{escaped}%");
+
+            query_as!(
+                NameMatch,
+                r#"SELECT user_id AS "user_id!", kind AS "kind!", name AS "name!", guild_id,
+                   MIN(timestamp) AS "first_held!", MAX(timestamp) AS "last_held!"
+                   FROM name_history WHERE LOWER(name) LIKE $1
+                   GROUP BY user_id, kind, name, guild_id ORDER BY MAX(timestamp) DESC LIMIT $2"#,
+                pattern,
+                SEARCH_LIMIT
+            )
+            .fetch_all(db)
+            .await?
+        }
+        SearchMode::Fuzzy => {
+            query_as!(
+                NameMatch,
+                r#"SELECT user_id AS "user_id!", kind AS "kind!", name AS "name!", guild_id,
+                   MIN(timestamp) AS "first_held!", MAX(timestamp) AS "last_held!"
+                   FROM name_history WHERE LOWER(name) % LOWER($1)
+                   GROUP BY user_id, kind, name, guild_id
+                   ORDER BY similarity(LOWER(name), LOWER($1)) DESC LIMIT $2"#,
+                name,
+                SEARCH_LIMIT
+            )
+            .fetch_all(db)
+            .await?
+        }
+        SearchMode::Confusable => {
+            query_as!(
+                NameMatch,
+                r#"SELECT user_id AS "user_id!", kind AS "kind!", name AS "name!", guild_id,
+                   MIN(timestamp) AS "first_held!", MAX(timestamp) AS "last_held!"
+                   FROM name_history WHERE skeleton = $1
+                   GROUP BY user_id, kind, name, guild_id ORDER BY MAX(timestamp) DESC LIMIT $2"#,
+                moth_filter::skeleton(&name),
+                SEARCH_LIMIT
+            )
+            .fetch_all(db)
+            .await?
+        }
+    };
+
+    if records.is_empty() {
+        ctx.say("Nobody has held a matching name.").await?;
+        return Ok(());
+    }
+
+    let cache = ctx.cache();
+    let lines = records
+        .iter()
+        .map(|r| {
+            let guild = r.guild_id.map_or_else(String::new, |id| {
+                let id = GuildId::new(id as u64);
+                let name = id.name(cache).unwrap_or_else(|| id.to_string());
+                format!(" in {name}")
+            });
+
+            format!(
+                "<@{0}> ({0}) **{1}**{guild}: {2}\n<t:{3}:d> to <t:{4}:d>",
+                r.user_id as u64,
+                r.kind,
+                r.name,
+                r.first_held.and_utc().timestamp(),
+                r.last_held.and_utc().timestamp()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let users = records.iter().map(|r| r.user_id).collect::<HashSet<_>>();
+    let footer = format!("{} users, {:?} match", users.len(), mode);
+    let title = format!("Names matching {name}");
+    paginate_lines(ctx, &title, &lines, Some(&footer)).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [names(), name_search()]
+}
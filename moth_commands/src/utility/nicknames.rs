@@ -0,0 +1,71 @@
+use crate::utils::paginate_lines;
+use crate::{Context, Error};
+use moth_events::handlers::users::nickname::{enforce, shown_name};
+use moth_events::moderation::guild_config;
+
+/// Show who the nickname policy would rename, without renaming anyone.
+#[poise::command(
+    rename = "nickname-report",
+    slash_command,
+    prefix_command,
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "MANAGE_NICKNAMES",
+    ephemeral
+)]
+pub async fn nickname_report(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let policy = guild_config(&ctx.data(), guild_id, |e| e.nickname_policy.as_ref());
+    let configured = policy.is_some();
+    let policy = policy.unwrap_or_default();
+
+    let renames = {
+        let Some(guild) = ctx.cache().guild(guild_id) else {
+            return Err("This server isn't cached.".into());
+        };
+
+        guild
+            .members
+            .iter()
+            .filter(|m| !m.user.bot())
+            .filter(|m| !m.roles.iter().any(|r| policy.exempt_roles.contains(r)))
+            .filter_map(|m| {
+                let name = shown_name(m);
+                let rename = enforce(&policy, name, &m.user.name)?;
+                Some(format!(
+                    "<@{}> `{name}` -> `{}` ({})",
+                    m.user.id,
+                    rename.name,
+                    rename.reasons()
+                ))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if renames.is_empty() {
+        ctx.say("No cached members break the nickname policy.")
+            .await?;
+        return Ok(());
+    }
+
+    let footer = if configured {
+        format!("{} members would be renamed", renames.len())
+    } else {
+        format!(
+            "{} members would be renamed, this server has no policy so the defaults were used",
+            renames.len()
+        )
+    };
+
+    paginate_lines(ctx, "Nickname policy report", &renames, Some(&footer)).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [nickname_report()]
+}
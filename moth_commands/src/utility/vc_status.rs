@@ -0,0 +1,74 @@
+use crate::utils::paginate_lines;
+use crate::{Context, Error};
+use poise::serenity_prelude::GuildChannel;
+use sqlx::query;
+
+/// Show the past statuses of a voice channel and who set them.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "vc-status-history",
+    aliases("status-history"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    user_cooldown = 5
+)]
+pub async fn vc_status_history(
+    ctx: Context<'_>,
+    #[description = "The voice channel to check"]
+    #[channel_types("Voice")]
+    channel: GuildChannel,
+) -> Result<(), Error> {
+    if Some(channel.guild_id) != ctx.guild_id() {
+        ctx.say("That channel is not in this server.").await?;
+        return Ok(());
+    }
+
+    let records = query!(
+        "SELECT user_id, new_status, blacklisted, cleared, timestamp FROM vc_status_history \
+         WHERE channel_id = $1 ORDER BY timestamp DESC",
+        channel.id.get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    if records.is_empty() {
+        ctx.say("I have no status history for this channel.")
+            .await?;
+        return Ok(());
+    }
+
+    let lines = records
+        .iter()
+        .map(|r| {
+            let timestamp = r.timestamp.and_utc().timestamp();
+            let author = r
+                .user_id
+                .map_or_else(|| String::from("Unknown"), |id| format!("<@{id}>"));
+            let status = match &r.new_status {
+                Some(status) => format!("`{}`", status.replace('`', "'")),
+                None => String::from("*cleared*"),
+            };
+
+            let mut line = format!("<t:{timestamp}:R> {author}: {status}");
+            if r.cleared {
+                line.push_str(" (blacklisted, removed)");
+            } else if r.blacklisted {
+                line.push_str(" (blacklisted)");
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+
+    let title = format!("Status history of #{}", channel.name);
+    paginate_lines(ctx, &title, &lines, None).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [vc_status_history()]
+}
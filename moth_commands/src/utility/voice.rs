@@ -0,0 +1,210 @@
+use std::fmt::Write;
+
+use crate::utils::{format_duration, paginate_lines};
+use crate::{Context, Error};
+use chrono::Utc;
+use poise::serenity_prelude::{self as serenity, User};
+use sqlx::query;
+
+const TOP_CHANNELS: usize = 5;
+const LEADERBOARD_SIZE: i64 = 10;
+
+/// Show how much time a user or this server has spent in voice.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "voice-stats",
+    aliases("vc-stats", "voicestats"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    user_cooldown = 5
+)]
+pub async fn voice_stats(
+    ctx: Context<'_>,
+    #[description = "The user to show voice stats for, the whole server if not set"] user: Option<
+        User,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let db = &ctx.data().database.db;
+    let now = Utc::now().naive_utc();
+
+    let channels = query!(
+        r#"SELECT channel_id,
+             SUM(EXTRACT(EPOCH FROM (COALESCE(left_at, $3) - joined_at)))::BIGINT AS "seconds!",
+             COUNT(*) FILTER (WHERE switched_from IS NULL) AS "sessions!",
+             COUNT(switched_from) AS "switches!"
+           FROM voice_sessions
+           WHERE guild_id = $1 AND ($2::BIGINT IS NULL OR user_id = $2)
+           GROUP BY channel_id ORDER BY "seconds!" DESC"#,
+        guild_id.get() as i64,
+        user.as_ref().map(|u| u.id.get() as i64),
+        now
+    )
+    .fetch_all(db)
+    .await?;
+
+    if channels.is_empty() {
+        ctx.say("I have no voice history for this yet.").await?;
+        return Ok(());
+    }
+
+    let total = channels.iter().map(|c| c.seconds).sum::<i64>();
+    let sessions = channels.iter().map(|c| c.sessions).sum::<i64>();
+    let switches = channels.iter().map(|c| c.switches).sum::<i64>();
+
+    let activity = query!(
+        r#"SELECT
+             COALESCE(SUM(EXTRACT(EPOCH FROM (COALESCE(ended_at, $3) - started_at)))
+               FILTER (WHERE kind = 'Stream'), 0)::BIGINT AS "stream!",
+             COALESCE(SUM(EXTRACT(EPOCH FROM (COALESCE(ended_at, $3) - started_at)))
+               FILTER (WHERE kind = 'Video'), 0)::BIGINT AS "video!"
+           FROM voice_activity
+           WHERE guild_id = $1 AND ($2::BIGINT IS NULL OR user_id = $2)"#,
+        guild_id.get() as i64,
+        user.as_ref().map(|u| u.id.get() as i64),
+        now
+    )
+    .fetch_one(db)
+    .await?;
+
+    let mut favourites = String::new();
+    for channel in channels.iter().take(TOP_CHANNELS) {
+        writeln!(
+            favourites,
+            "<#{}>: {}",
+            channel.channel_id,
+            format_duration(channel.seconds.max(0) as u64)
+        )
+        .unwrap();
+    }
+
+    let title = match &user {
+        Some(user) => format!("Voice stats of {}", user.tag()),
+        None => String::from("Voice stats of this server"),
+    };
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(title)
+        .field("Total time", format_duration(total.max(0) as u64), true)
+        .field("Sessions", sessions.to_string(), true)
+        .field("Channel switches", switches.to_string(), true)
+        .field(
+            "Streaming",
+            format_duration(activity.stream.max(0) as u64),
+            true,
+        )
+        .field(
+            "Camera",
+            format_duration(activity.video.max(0) as u64),
+            true,
+        )
+        .field("Favourite channels", favourites, false);
+
+    if user.is_none() {
+        let leaderboard = query!(
+            r#"SELECT user_id,
+                 SUM(EXTRACT(EPOCH FROM (COALESCE(left_at, $2) - joined_at)))::BIGINT AS "seconds!"
+               FROM voice_sessions WHERE guild_id = $1
+               GROUP BY user_id ORDER BY "seconds!" DESC LIMIT $3"#,
+            guild_id.get() as i64,
+            now,
+            LEADERBOARD_SIZE
+        )
+        .fetch_all(db)
+        .await?;
+
+        let mut lines = String::new();
+        for (index, record) in leaderboard.iter().enumerate() {
+            writeln!(
+                lines,
+                "{}. <@{}>: {}",
+                index + 1,
+                record.user_id,
+                format_duration(record.seconds.max(0) as u64)
+            )
+            .unwrap();
+        }
+
+        embed = embed.field("Leaderboard", lines, false);
+    }
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Show who is in voice right now and for how long.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "in-voice",
+    aliases("invoice", "in-vc"),
+    category = "Utility",
+    guild_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    user_cooldown = 5
+)]
+pub async fn in_voice(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let states = {
+        let Some(guild) = ctx.guild() else {
+            return Err("Could not retrieve guild from cache.".into());
+        };
+
+        guild
+            .voice_states
+            .iter()
+            .filter_map(|v| v.channel_id.map(|c| (v.user_id, c)))
+            .collect::<Vec<_>>()
+    };
+
+    if states.is_empty() {
+        ctx.say("Nobody is in voice right now.").await?;
+        return Ok(());
+    }
+
+    let open = query!(
+        "SELECT user_id, joined_at FROM voice_sessions WHERE guild_id = $1 AND left_at IS NULL",
+        guild_id.get() as i64
+    )
+    .fetch_all(&ctx.data().database.db)
+    .await?;
+
+    let now = Utc::now().naive_utc();
+    let mut entries = states
+        .into_iter()
+        .map(|(user_id, channel_id)| {
+            let seconds = open
+                .iter()
+                .find(|r| r.user_id as u64 == user_id.get())
+                .map(|r| (now - r.joined_at).num_seconds().max(0) as u64);
+            (user_id, channel_id, seconds)
+        })
+        .collect::<Vec<_>>();
+
+    // the longest sessions first, members without a known session last.
+    entries.sort_unstable_by(|a, b| b.2.cmp(&a.2));
+
+    let lines = entries
+        .iter()
+        .map(|(user_id, channel_id, seconds)| {
+            let duration = seconds.map_or_else(|| String::from("unknown"), format_duration);
+            format!("<@{user_id}> in <#{channel_id}> for {duration}")
+        })
+        .collect::<Vec<_>>();
+
+    let footer = format!("{} members in voice", lines.len());
+    paginate_lines(ctx, "In voice right now", &lines, Some(&footer)).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [voice_stats(), in_voice()]
+}
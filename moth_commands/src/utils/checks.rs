@@ -1,8 +1,9 @@
-use crate::{Command, Data};
+use crate::{Command, Context, Data, Error};
 
 use std::sync::Arc;
 
-use poise::serenity_prelude::User;
+use poise::serenity_prelude::{Permissions, User};
+use poise::PrefixContext;
 
 pub enum CommandRestrictErr {
     CommandNotFound,
@@ -104,3 +105,43 @@ pub async fn handle_deny_cmd(
 
     Ok(command_name)
 }
+
+/// Gets the permissions of the invoking member in the current channel.
+pub async fn author_permissions(ctx: Context<'_>) -> Result<Permissions, Error> {
+    let permissions = match ctx {
+        poise::Context::Application(ctx) => ctx
+            .interaction
+            .member
+            .as_ref()
+            .unwrap()
+            .permissions
+            .unwrap(),
+        poise::Context::Prefix(ctx) => prefix_member_perms(ctx).await?,
+    };
+
+    Ok(permissions)
+}
+
+async fn prefix_member_perms(
+    ctx: PrefixContext<'_, crate::Data, Error>,
+) -> Result<Permissions, Error> {
+    let Some(guild) = ctx.guild() else {
+        return Err("Could not retrieve guild from cache.".into());
+    };
+
+    let channel_id = ctx.channel_id();
+    let channel = guild
+        .channels
+        .get(&channel_id)
+        .or_else(|| guild.threads.iter().find(|c| c.id == channel_id))
+        .expect("Channels or threads are always sent alongside the guild.");
+
+    Ok(guild.partial_member_permissions_in(
+        channel,
+        ctx.author().id,
+        ctx.msg
+            .member
+            .as_ref()
+            .expect("PartialMember is always present on a message from a guild."),
+    ))
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use chrono::Utc;
+
+/// The first second of 2015, which Discord snowflakes are relative to.
+const DISCORD_EPOCH: u64 = 1420070400000;
+
+/// Parses a human duration like `7d`, `3mo` or `1w2d` into a [`Duration`].
+///
+/// Supported units are `s`, `m`/`min`, `h`, `d`, `w`, `mo` (30 days) and `y` (365 days).
+/// Returns `None` if the input is empty, contains an unknown unit or overflows.
+#[must_use]
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+
+    let mut total: u64 = 0;
+    let mut chars = input.chars().filter(|c| !c.is_whitespace()).peekable();
+
+    while chars.peek().is_some() {
+        let mut number = String::new();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            number.push(*c);
+            chars.next();
+        }
+
+        let mut unit = String::new();
+        while let Some(c) = chars.peek().filter(|c| c.is_alphabetic()) {
+            unit.push(*c);
+            chars.next();
+        }
+
+        if number.is_empty() || unit.is_empty() {
+            return None;
+        }
+
+        let multiplier = match unit.as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "d" | "day" | "days" => 60 * 60 * 24,
+            "w" | "week" | "weeks" => 60 * 60 * 24 * 7,
+            "mo" | "month" | "months" => 60 * 60 * 24 * 30,
+            "y" | "year" | "years" => 60 * 60 * 24 * 365,
+            _ => return None,
+        };
+
+        let value = number.parse::<u64>().ok()?.checked_mul(multiplier)?;
+        total = total.checked_add(value)?;
+    }
+
+    Some(Duration::from_secs(total))
+}
+
+/// Returns the unix timestamp `duration` ago, or 0 if no duration is given.
+#[must_use]
+pub fn unix_since(duration: Option<Duration>) -> i64 {
+    duration.map_or(0, |d| {
+        Utc::now()
+            .timestamp()
+            .saturating_sub(i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+            .max(0)
+    })
+}
+
+/// The lowest possible snowflake created at the given unix timestamp.
+///
+/// Useful for filtering tables that only store message ids by time.
+#[must_use]
+pub fn snowflake_from_unix(timestamp: i64) -> i64 {
+    let millis = (timestamp.max(0) as u64 * 1000).saturating_sub(DISCORD_EPOCH);
+    (millis << 22) as i64
+}
+
+/// Converts a snowflake back into the unix timestamp it was created at.
+#[must_use]
+pub fn unix_from_snowflake(snowflake: i64) -> i64 {
+    (((snowflake as u64) >> 22) + DISCORD_EPOCH) as i64 / 1000
+}
+
+/// Formats a number of seconds as a short human duration like `2d 3h 4m`.
+///
+/// Leading zero units are skipped and seconds are only shown under an hour.
+#[must_use]
+pub fn format_duration(seconds: u64) -> String {
+    let (minutes, secs) = (seconds / 60, seconds % 60);
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let (days, hours) = (hours / 24, hours % 24);
+
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
@@ -1,9 +1,11 @@
 pub mod checks;
+pub mod duration;
 pub mod pagination;
 
 use std::time::Duration;
 
 pub use checks::*;
+pub use duration::*;
 use moth_data::structs::{Context, Error};
 pub use pagination::*;
 use poise::CreateReply;
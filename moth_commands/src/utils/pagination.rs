@@ -12,23 +12,91 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     total_members: usize,
     total_games: usize,
 ) -> Result<(), serenity::Error> {
-    let ctx_id = ctx.id();
-    let prev_button_id = format!("{ctx_id}prev");
-    let next_button_id = format!("{ctx_id}next");
+    let footer = format!("{total_members} members are playing {total_games} games right now.");
+
+    let embeds = (0..pages.len())
+        .map(|page| create_presence_embed(page, &footer, &pages))
+        .collect::<Vec<_>>();
+
+    paginate_embeds(ctx, &embeds).await
+}
+
+// This is split to make the code more pleasant
+fn create_presence_embed<'a>(
+    current_page: usize,
+    footer_text: &str,
+    pages: &[Vec<(&str, u32)>],
+) -> serenity::CreateEmbed<'a> {
+    serenity::CreateEmbed::default()
+        .title("Top games being played right now:")
+        .description(format_pages(&pages[current_page]))
+        .footer(CreateEmbedFooter::new(footer_text.to_string()))
+}
+
+fn format_pages(pages: &[(&str, u32)]) -> String {
+    pages
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+const LINES_PER_PAGE: usize = 20;
 
-    let sctx = ctx.serenity_context();
+/// Displays a list of lines in an embed, paginating with buttons if there are too many.
+pub async fn paginate_lines<U: Send + Sync + 'static, E>(
+    ctx: Context<'_, U, E>,
+    title: &str,
+    lines: &[String],
+    footer: Option<&str>,
+) -> Result<(), serenity::Error> {
+    let pages = lines.chunks(LINES_PER_PAGE).collect::<Vec<_>>();
+    let total_pages = pages.len().max(1);
+
+    let embeds = (0..total_pages)
+        .map(|page| {
+            create_lines_embed(
+                title,
+                pages.get(page).copied().unwrap_or_default(),
+                page,
+                total_pages,
+                footer,
+            )
+        })
+        .collect::<Vec<_>>();
 
+    paginate_embeds(ctx, &embeds).await
+}
+
+/// Displays prebuilt embeds one at a time, paginating with buttons if there are multiple.
+pub async fn paginate_embeds<U: Send + Sync + 'static, E>(
+    ctx: Context<'_, U, E>,
+    embeds: &[serenity::CreateEmbed<'_>],
+) -> Result<(), serenity::Error> {
+    let Some(first) = embeds.first() else {
+        return Ok(());
+    };
+
+    let builder = CreateReply::default().embed(first.clone());
+
+    if embeds.len() == 1 {
+        ctx.send(builder).await?;
+        return Ok(());
+    }
+
+    let total_pages = embeds.len();
     let mut current_page = 0;
-    let footer = format!("{total_members} members are playing {total_games} games right now.");
+
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{ctx_id}prev");
+    let next_button_id = format!("{ctx_id}next");
 
     let msg = ctx
         .send(
-            poise::CreateReply::default()
-                .embed(create_presence_embed(current_page, &footer, &pages))
-                .components(vec![CreateActionRow::Buttons(Cow::Owned(vec![
-                    serenity::CreateButton::new(&prev_button_id).emoji('◀'),
-                    serenity::CreateButton::new(&next_button_id).emoji('▶'),
-                ]))]),
+            builder.components(vec![CreateActionRow::Buttons(Cow::Owned(vec![
+                serenity::CreateButton::new(&prev_button_id).emoji('◀'),
+                serenity::CreateButton::new(&next_button_id).emoji('▶'),
+            ]))]),
         )
         .await?;
 
@@ -39,29 +107,30 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     {
         if press.data.custom_id == next_button_id {
             current_page += 1;
-            if current_page >= pages.len() {
+            if current_page >= total_pages {
                 current_page = 0;
             }
         } else if press.data.custom_id == prev_button_id {
-            current_page = current_page.checked_sub(1).unwrap_or(pages.len() - 1);
+            current_page = current_page.checked_sub(1).unwrap_or(total_pages - 1);
         } else {
             continue;
         }
 
         press
             .create_response(
-                &sctx.http,
+                &ctx.serenity_context().http,
                 CreateInteractionResponse::UpdateMessage(
                     serenity::CreateInteractionResponseMessage::default()
-                        .embed(create_presence_embed(current_page, &footer, &pages)),
+                        .embed(embeds[current_page].clone()),
                 ),
             )
             .await?;
     }
+
     msg.edit(
         ctx,
         CreateReply::default()
-            .embed(create_presence_embed(current_page, &footer, &pages))
+            .embed(embeds[current_page].clone())
             .components(vec![]),
     )
     .await?;
@@ -69,22 +138,23 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     Ok(())
 }
 
-// This is split to make the code more pleasant
-fn create_presence_embed<'a>(
+fn create_lines_embed<'a>(
+    title: &str,
+    lines: &[String],
     current_page: usize,
-    footer_text: &str,
-    pages: &[Vec<(&str, u32)>],
+    total_pages: usize,
+    footer: Option<&str>,
 ) -> serenity::CreateEmbed<'a> {
-    serenity::CreateEmbed::default()
-        .title("Top games being played right now:")
-        .description(format_pages(&pages[current_page]))
-        .footer(CreateEmbedFooter::new(footer_text.to_string()))
-}
+    let footer = match footer {
+        Some(footer) if total_pages > 1 => {
+            format!("Page {}/{total_pages} • {footer}", current_page + 1)
+        }
+        Some(footer) => footer.to_string(),
+        None => format!("Page {}/{total_pages}", current_page + 1),
+    };
 
-fn format_pages(pages: &[(&str, u32)]) -> String {
-    pages
-        .iter()
-        .map(|(name, count)| format!("{name}: {count}"))
-        .collect::<Vec<String>>()
-        .join("\n")
+    serenity::CreateEmbed::default()
+        .title(title.to_string())
+        .description(lines.join("\n"))
+        .footer(CreateEmbedFooter::new(footer))
 }
@@ -2,13 +2,15 @@
 
 use std::collections::{HashMap, HashSet};
 
+use moth_filter::WordLists;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use poise::serenity_prelude::{ChannelId, GuildId};
+use poise::serenity_prelude::{ChannelId, GuildId, RoleId};
 
 mod serialize;
-use serialize::{read_words_from_file, regex_patterns};
+use serialize::regex_patterns;
+pub use serialize::{parse_words, read_words_from_file};
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct MothConfig {
@@ -22,7 +24,10 @@ impl MothConfig {
     #[must_use]
     pub fn new() -> Self {
         MothConfig {
-            events: Events::default(),
+            events: Events {
+                invite_moderation: default_invite_moderation(),
+                ..Default::default()
+            },
             vcstatus: VCStatus::default(),
         }
     }
@@ -49,15 +54,19 @@ impl MothConfig {
 
     #[must_use]
     pub fn load_config() -> Self {
+        let mut config = MothConfig::read_config();
+        config
+            .events
+            .set_global_filter_lists(FilterLists::from_files());
+        config
+    }
+
+    fn read_config() -> Self {
         let default_config = MothConfig::new();
 
         let config_result = std::fs::read_to_string("config/config.json");
         if let Ok(config_file) = config_result {
-            if let Ok(mut config) = serde_json::from_str::<MothConfig>(&config_file) {
-                // Set value of unconfigurable properties.
-                config.events.badlist = read_words_from_file("config/lists/badwords.txt");
-                config.events.fixlist = read_words_from_file("config/lists/fixwords.txt");
-
+            if let Ok(config) = serde_json::from_str::<MothConfig>(&config_file) {
                 config
             } else {
                 eprintln!("Error: Failed to parse config.json. Using default configuration.");
@@ -78,6 +87,9 @@ pub struct VCStatus {
     pub action: bool,
     pub post_channel: Option<ChannelId>,
     pub blacklist_detection: bool,
+    /// Clears statuses that match the blacklist.
+    #[serde(default)]
+    pub clear_blacklisted: bool,
     pub announce_channel: Option<ChannelId>,
     #[serde(with = "regex_patterns")]
     pub regex: Option<Vec<Regex>>,
@@ -90,9 +102,395 @@ pub struct Events {
     pub no_log_users: Option<Vec<u64>>,
     #[serde(with = "regex_patterns")]
     pub regex: Option<Vec<Regex>>,
+    /// The filter word lists of each guild, loaded from the database.
+    ///
+    /// Set with [`Events::set_filter_lists`] so the merged lists stay in sync.
     #[serde(skip)]
-    pub badlist: HashSet<String>,
+    pub filter_lists: HashMap<GuildId, FilterLists>,
+    /// The global lists merged with the lists of each guild, ready for matching.
     #[serde(skip)]
-    pub fixlist: HashSet<String>,
+    merged_filter_lists: HashMap<GuildId, WordLists>,
+    /// Words from `config/lists`, used everywhere including DMs alongside each guilds own lists.
+    #[serde(skip)]
+    global_filter_lists: FilterLists,
+    #[serde(skip)]
+    global_word_lists: WordLists,
     pub guild_name_override: Option<HashMap<GuildId, String>>,
+    /// Guilds that have all member names synced on guild create and member chunks.
+    pub name_sync_guilds: Option<Vec<GuildId>>,
+    /// Folder to download avatars and banners into when they change.
+    pub avatar_archive: Option<String>,
+    /// Guilds where the invite used by joining members is tracked.
+    pub invite_tracking_guilds: Option<Vec<GuildId>>,
+    /// Per guild configuration of how invites posted in messages are handled.
+    ///
+    /// Defaults to logging invites posted in osu!game, set to `null` to turn that off.
+    #[serde(default = "default_invite_moderation")]
+    pub invite_moderation: Option<HashMap<GuildId, InviteModeration>>,
+    /// Per guild configuration of mass deletion detection.
+    pub anti_delete: Option<HashMap<GuildId, AntiDelete>>,
+    /// Per guild configuration of join raid detection.
+    pub raid_detection: Option<HashMap<GuildId, RaidDetection>>,
+    /// Per guild configuration of repeated message detection.
+    pub spam_detection: Option<HashMap<GuildId, SpamDetection>>,
+    /// Per guild configuration of what is done with messages the filter flags.
+    pub filter_moderation: Option<HashMap<GuildId, FilterModeration>>,
+    /// Per guild configuration of how names, statuses and thread titles are filtered.
+    pub name_filtering: Option<HashMap<GuildId, NameFiltering>>,
+    /// Per guild nickname rules enforced when members join or change their name.
+    pub nickname_policy: Option<HashMap<GuildId, NicknamePolicy>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FilterModeration {
+    pub log_channel: Option<ChannelId>,
+    /// Actions for each severity rustrict rates messages as, nothing is done for `null`.
+    pub mild: Option<ModerationAction>,
+    pub moderate: Option<ModerationAction>,
+    pub severe: Option<ModerationAction>,
+    /// Action for messages with words on the guilds badlist.
+    pub badlist: Option<ModerationAction>,
+    pub exempt_channels: Vec<ChannelId>,
+    pub bypass_roles: Vec<RoleId>,
+    pub timeout_secs: u64,
+}
+
+impl Default for FilterModeration {
+    fn default() -> Self {
+        FilterModeration {
+            log_channel: None,
+            mild: None,
+            moderate: Some(ModerationAction::Log),
+            severe: Some(ModerationAction::Delete),
+            badlist: Some(ModerationAction::Log),
+            exempt_channels: Vec::new(),
+            bypass_roles: Vec::new(),
+            timeout_secs: 600,
+        }
+    }
+}
+
+/// What to do with a name, status or title the filter flagged, each action includes the ones
+/// before it.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NameFilterAction {
+    #[default]
+    None,
+    /// Only post to the log channel.
+    Log,
+    /// Undo it, by resetting the nickname, clearing the voice channel status or renaming the
+    /// thread back.
+    Reset,
+    /// Undo it and time out whoever set it.
+    Timeout,
+}
+
+/// What to do with a voice channel status the filter flagged.
+///
+/// Who set a status isn't known when it is received, so there is nobody to time out.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VcStatusFilterAction {
+    #[default]
+    None,
+    /// Only post to the log channel.
+    Log,
+    /// Clear the status.
+    Reset,
+}
+
+/// How bad rustrict has to rate something before it is flagged.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MinSeverity {
+    Mild,
+    #[default]
+    Moderate,
+    Severe,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NameFiltering {
+    pub log_channel: Option<ChannelId>,
+    pub nicknames: NameFilterAction,
+    /// Global names can't be changed, so resetting gives the member the fallback nickname.
+    pub global_names: NameFilterAction,
+    pub vc_statuses: VcStatusFilterAction,
+    /// Thread and forum post titles, new threads can't be renamed back so they are locked.
+    pub thread_titles: NameFilterAction,
+    /// Custom statuses can't be changed, so resetting only logs them.
+    pub custom_statuses: NameFilterAction,
+    /// The least severe rating that is flagged, the badlist is always flagged.
+    pub min_severity: MinSeverity,
+    /// The nickname given to members whose name can't be reset.
+    pub fallback_name: String,
+    pub bypass_roles: Vec<RoleId>,
+    pub timeout_secs: u64,
+}
+
+impl Default for NameFiltering {
+    fn default() -> Self {
+        NameFiltering {
+            log_channel: None,
+            nicknames: NameFilterAction::Log,
+            global_names: NameFilterAction::Log,
+            vc_statuses: VcStatusFilterAction::Log,
+            thread_titles: NameFilterAction::Log,
+            custom_statuses: NameFilterAction::Log,
+            min_severity: MinSeverity::Moderate,
+            fallback_name: String::from("Moderated name"),
+            bypass_roles: Vec::new(),
+            timeout_secs: 600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NicknamePolicy {
+    pub log_channel: Option<ChannelId>,
+    /// Remove punctuation, symbols and invisible characters from the start of names.
+    pub dehoist: bool,
+    /// Replace names made only of lookalikes of ascii with the ascii they look like.
+    pub replace_confusable: bool,
+    /// Replace names that can't be typed with the members username.
+    pub replace_unmentionable: bool,
+    pub max_length: Option<usize>,
+    /// Used when the username can't be used either.
+    pub fallback_name: String,
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl Default for NicknamePolicy {
+    fn default() -> Self {
+        NicknamePolicy {
+            log_channel: None,
+            dehoist: true,
+            replace_confusable: true,
+            replace_unmentionable: true,
+            max_length: None,
+            fallback_name: String::from("Member"),
+            exempt_roles: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpamDetection {
+    pub alert_channel: Option<ChannelId>,
+    /// Seconds a message is remembered for.
+    pub window_secs: u64,
+    /// How many channels one user can post the same message in.
+    pub channel_threshold: usize,
+    /// How many times one user can post the same message.
+    pub repeat_threshold: usize,
+    /// How many users can post the same message before they are treated as compromised.
+    pub user_threshold: usize,
+    /// Messages shorter than this without attachments are ignored when comparing users, so
+    /// common replies aren't flagged.
+    pub min_length: usize,
+    pub exempt_channels: Vec<ChannelId>,
+    pub bypass_roles: Vec<RoleId>,
+    pub action: ModerationAction,
+    pub timeout_secs: u64,
+}
+
+impl Default for SpamDetection {
+    fn default() -> Self {
+        SpamDetection {
+            alert_channel: None,
+            window_secs: 30,
+            channel_threshold: 3,
+            repeat_threshold: 5,
+            user_threshold: 3,
+            min_length: 20,
+            exempt_channels: Vec::new(),
+            bypass_roles: Vec::new(),
+            action: ModerationAction::Log,
+            timeout_secs: 3600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FilterLists {
+    /// Words that are flagged when found in a word.
+    pub badlist: HashSet<String>,
+    /// Words that stop a word from being flagged by the badlist.
+    pub fixlist: HashSet<String>,
+}
+
+impl FilterLists {
+    /// Reads the global lists, leaving out any that can't be read.
+    #[must_use]
+    pub fn from_files() -> Self {
+        let read = |filename| match read_words_from_file(filename) {
+            Ok(words) => words,
+            Err(e) => {
+                eprintln!("Error: Failed to read {filename}: {e}");
+                HashSet::new()
+            }
+        };
+
+        FilterLists {
+            badlist: read("config/lists/badwords.txt"),
+            fixlist: read("config/lists/fixwords.txt"),
+        }
+    }
+}
+
+impl FilterLists {
+    fn word_lists(&self) -> WordLists {
+        WordLists::new(&self.badlist, &self.fixlist)
+    }
+}
+
+impl Events {
+    /// Replaces the global lists, which have to be set before any guilds lists are.
+    pub fn set_global_filter_lists(&mut self, lists: FilterLists) {
+        self.global_word_lists = lists.word_lists();
+        self.global_filter_lists = lists;
+    }
+
+    /// Replaces the lists of a guild, merging them with the global lists once up front.
+    pub fn set_filter_lists(&mut self, guild_id: GuildId, lists: FilterLists) {
+        let mut merged = self.global_filter_lists.clone();
+        merged.badlist.extend(lists.badlist.iter().cloned());
+        merged.fixlist.extend(lists.fixlist.iter().cloned());
+
+        self.merged_filter_lists
+            .insert(guild_id, merged.word_lists());
+        self.filter_lists.insert(guild_id, lists);
+    }
+
+    /// The global lists merged with the lists of the guild, if any.
+    #[must_use]
+    pub fn filter_lists_for(&self, guild_id: Option<GuildId>) -> &WordLists {
+        guild_id
+            .and_then(|g| self.merged_filter_lists.get(&g))
+            .unwrap_or(&self.global_word_lists)
+    }
+}
+
+/// What to do with members that joined during a raid.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Default, PartialEq, Eq)]
+pub enum RaidAction {
+    /// Only list them in the alert.
+    #[default]
+    None,
+    Timeout,
+    Kick,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RaidDetection {
+    pub alert_channel: Option<ChannelId>,
+    /// Heat at which the joins are treated as a raid, every join adds 1 plus 1 per signal.
+    pub threshold: u32,
+    /// Seconds without joins before heat starts to decay, after which it halves every 2 seconds.
+    pub decay_secs: u64,
+    /// Accounts younger than this count as new.
+    pub new_account_days: u64,
+    /// How similar two names in the burst must be to count as a signal, from 0 to 1.
+    pub name_similarity: f64,
+    /// Raises the verification level to high while the raid lasts.
+    pub raise_verification: bool,
+    pub action: RaidAction,
+    pub timeout_secs: u64,
+}
+
+impl Default for RaidDetection {
+    fn default() -> Self {
+        RaidDetection {
+            alert_channel: None,
+            threshold: 15,
+            decay_secs: 10,
+            new_account_days: 7,
+            name_similarity: 0.8,
+            raise_verification: false,
+            action: RaidAction::None,
+            timeout_secs: 3600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AntiDelete {
+    /// Heat at which the authors of deleted messages are looked up and an alert is sent.
+    pub check_heat: u16,
+    /// The most heat a guild can build up, which limits how long a burst takes to cool down.
+    pub max_heat: u16,
+    /// Seconds without deletions before heat starts to decay.
+    pub decay_secs: u64,
+    pub alert_channel: Option<ChannelId>,
+}
+
+impl Default for AntiDelete {
+    fn default() -> Self {
+        AntiDelete {
+            check_heat: 3,
+            max_heat: 5,
+            decay_secs: 5,
+            alert_channel: None,
+        }
+    }
+}
+
+/// What to do with content that breaks a rule, from least to most severe.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ModerationAction {
+    /// Only post to the log channel.
+    #[default]
+    Log,
+    Delete,
+    /// Delete and tell the author why.
+    Warn,
+    /// Delete and time out the author for the configured duration.
+    DeleteAndTimeout,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct InviteModeration {
+    pub log_channel: Option<ChannelId>,
+    /// Where expired or invalid invites are logged, nowhere if not set.
+    pub invalid_log_channel: Option<ChannelId>,
+    /// Guilds that can always be advertised, such as partner servers.
+    #[serde(default)]
+    pub allowed_guilds: Vec<GuildId>,
+    /// Invite codes that can always be posted, such as vanity urls of partners.
+    #[serde(default)]
+    pub allowed_codes: Vec<String>,
+    /// Channels where invites are allowed.
+    #[serde(default)]
+    pub allowed_channels: Vec<ChannelId>,
+    /// Roles that are allowed to post invites anywhere.
+    #[serde(default)]
+    pub bypass_roles: Vec<RoleId>,
+    #[serde(default)]
+    pub action: ModerationAction,
+    /// Length of the timeout in seconds when using [`ModerationAction::DeleteAndTimeout`].
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    60 * 10
+}
+
+/// Invites posted in osu!game were always logged before this was configurable.
+#[allow(clippy::unnecessary_wraps, clippy::unreadable_literal)]
+fn default_invite_moderation() -> Option<HashMap<GuildId, InviteModeration>> {
+    let moderation = InviteModeration {
+        log_channel: Some(ChannelId::new(277163440999628800)),
+        timeout_secs: default_timeout_secs(),
+        ..Default::default()
+    };
+
+    Some(HashMap::from([(
+        GuildId::new(98226572468690944),
+        moderation,
+    )]))
 }
@@ -1,10 +1,17 @@
 use std::collections::HashSet;
 
-pub fn read_words_from_file(filename: &str) -> HashSet<String> {
-    std::fs::read_to_string(filename)
-        .expect("Failed to read the file")
+/// Reads a list of words, one per line.
+pub fn read_words_from_file(filename: &str) -> std::io::Result<HashSet<String>> {
+    Ok(parse_words(&std::fs::read_to_string(filename)?))
+}
+
+/// Parses a list of words, one per line, ignoring blank lines.
+#[must_use]
+pub fn parse_words(content: &str) -> HashSet<String> {
+    content
         .lines()
         .map(|line| line.trim().to_lowercase())
+        .filter(|line| !line.is_empty())
         .collect()
 }
 
@@ -0,0 +1,252 @@
+use chrono::{NaiveDateTime, Utc};
+use poise::serenity_prelude::{GuildId, ImageHash, User, UserId};
+use sqlx::query;
+
+use crate::lru::ShardedLru;
+use crate::structs::{Data, Error};
+
+/// Caches of the latest known image hashes, mirroring [`crate::structs::Names`].
+pub struct Avatars {
+    pub avatars: ShardedLru<UserId, Option<String>>,
+    pub banners: ShardedLru<UserId, Option<String>>,
+    pub guild_avatars: ShardedLru<(GuildId, UserId), Option<String>>,
+}
+
+impl Avatars {
+    #[must_use]
+    pub fn new(capacity: usize, guild_capacity: usize) -> Self {
+        Avatars {
+            avatars: ShardedLru::new(capacity),
+            banners: ShardedLru::new(capacity),
+            guild_avatars: ShardedLru::new(guild_capacity),
+        }
+    }
+}
+
+/// The kinds of images that have their history tracked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageKind {
+    Avatar,
+    Banner,
+    GuildAvatar(GuildId),
+}
+
+impl ImageKind {
+    /// The url of an image of this kind on the Discord CDN.
+    #[must_use]
+    pub fn cdn_url(self, user_id: UserId, hash: &str) -> String {
+        let ext = if hash.starts_with("a_") { "gif" } else { "png" };
+
+        match self {
+            ImageKind::Avatar => {
+                format!("https://cdn.discordapp.com/avatars/{user_id}/{hash}.{ext}?size=1024")
+            }
+            ImageKind::Banner => {
+                format!("https://cdn.discordapp.com/banners/{user_id}/{hash}.{ext}?size=1024")
+            }
+            ImageKind::GuildAvatar(guild_id) => format!(
+                "https://cdn.discordapp.com/guilds/{guild_id}/users/{user_id}/avatars/{hash}.\
+                 {ext}?size=1024"
+            ),
+        }
+    }
+
+    fn folder(self) -> String {
+        match self {
+            ImageKind::Avatar => String::from("avatars"),
+            ImageKind::Banner => String::from("banners"),
+            ImageKind::GuildAvatar(guild_id) => format!("guild_avatars/{guild_id}"),
+        }
+    }
+}
+
+impl Data {
+    /// Checks the global avatar and banner of a user, recording them if they changed.
+    ///
+    /// Banners are only sent in some payloads, so a missing banner is never recorded.
+    pub async fn check_or_insert_avatars(&self, user: &User) {
+        let avatar = user.avatar.as_ref().map(ImageHash::to_string);
+        let cached = self
+            .database
+            .avatars
+            .avatars
+            .insert(user.id, avatar.clone());
+        self.check_image(ImageKind::Avatar, user.id, cached, avatar)
+            .await;
+
+        if let Some(banner) = user.banner.as_ref().map(ImageHash::to_string) {
+            let cached = self
+                .database
+                .avatars
+                .banners
+                .insert(user.id, Some(banner.clone()));
+            self.check_image(ImageKind::Banner, user.id, cached, Some(banner))
+                .await;
+        }
+    }
+
+    /// Checks the guild specific avatar of a member, recording it if it changed.
+    pub async fn check_or_insert_guild_avatar(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        avatar: Option<&ImageHash>,
+    ) {
+        let avatar = avatar.map(ImageHash::to_string);
+        let cached = self
+            .database
+            .avatars
+            .guild_avatars
+            .insert((guild_id, user_id), avatar.clone());
+
+        self.check_image(ImageKind::GuildAvatar(guild_id), user_id, cached, avatar)
+            .await;
+    }
+
+    async fn check_image(
+        &self,
+        kind: ImageKind,
+        user_id: UserId,
+        // None if the hash was not cached, otherwise the previously cached hash.
+        cached: Option<Option<String>>,
+        hash: Option<String>,
+    ) {
+        // never insert if there is no image, same as names.
+        let Some(hash) = hash else {
+            return;
+        };
+
+        let changed = match cached {
+            Some(cached) => cached.as_deref() != Some(hash.as_str()),
+            None => self.get_latest_image_psql(kind, user_id).await.as_deref() != Some(&hash),
+        };
+
+        if changed && self.insert_image_db(kind, user_id, &hash).await.is_ok() {
+            self.maybe_archive_image(kind, user_id, hash);
+        }
+    }
+
+    async fn get_latest_image_psql(&self, kind: ImageKind, user_id: UserId) -> Option<String> {
+        let db = &self.database.db;
+
+        match kind {
+            ImageKind::Avatar => query!(
+                "SELECT avatar_hash FROM avatars WHERE user_id = $1 ORDER BY timestamp DESC LIMIT \
+                 1",
+                i64::from(user_id)
+            )
+            .fetch_optional(db)
+            .await
+            .ok()
+            .flatten()
+            .map(|r| r.avatar_hash),
+            ImageKind::Banner => query!(
+                "SELECT banner_hash FROM banners WHERE user_id = $1 ORDER BY timestamp DESC LIMIT \
+                 1",
+                i64::from(user_id)
+            )
+            .fetch_optional(db)
+            .await
+            .ok()
+            .flatten()
+            .map(|r| r.banner_hash),
+            ImageKind::GuildAvatar(guild_id) => query!(
+                "SELECT avatar_hash FROM guild_avatars WHERE guild_id = $1 AND user_id = $2 ORDER \
+                 BY timestamp DESC LIMIT 1",
+                i64::from(guild_id),
+                i64::from(user_id)
+            )
+            .fetch_optional(db)
+            .await
+            .ok()
+            .flatten()
+            .map(|r| r.avatar_hash),
+        }
+    }
+
+    async fn insert_image_db(
+        &self,
+        kind: ImageKind,
+        user_id: UserId,
+        hash: &str,
+    ) -> Result<(), Error> {
+        let timestamp: NaiveDateTime = Utc::now().naive_utc();
+        let db = &self.database.db;
+
+        self.database.insert_user(user_id).await?;
+
+        match kind {
+            ImageKind::Avatar => {
+                query!(
+                    "INSERT INTO avatars (user_id, avatar_hash, timestamp) VALUES ($1, $2, $3)",
+                    i64::from(user_id),
+                    hash,
+                    timestamp
+                )
+                .execute(db)
+                .await?;
+            }
+            ImageKind::Banner => {
+                query!(
+                    "INSERT INTO banners (user_id, banner_hash, timestamp) VALUES ($1, $2, $3)",
+                    i64::from(user_id),
+                    hash,
+                    timestamp
+                )
+                .execute(db)
+                .await?;
+            }
+            ImageKind::GuildAvatar(guild_id) => {
+                self.database.insert_guild(guild_id).await?;
+
+                query!(
+                    "INSERT INTO guild_avatars (guild_id, user_id, avatar_hash, timestamp) VALUES \
+                     ($1, $2, $3, $4)",
+                    i64::from(guild_id),
+                    i64::from(user_id),
+                    hash,
+                    timestamp
+                )
+                .execute(db)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the image into the archive folder if one is configured.
+    ///
+    /// Old images are not guaranteed to stay on the CDN, so this keeps a copy around.
+    fn maybe_archive_image(&self, kind: ImageKind, user_id: UserId, hash: String) {
+        let Some(archive) = self.config.read().events.avatar_archive.clone() else {
+            return;
+        };
+
+        let client = self.reqwest.clone();
+        tokio::spawn(async move {
+            let ext = if hash.starts_with("a_") { "gif" } else { "png" };
+            let folder = format!("{archive}/{}/{user_id}", kind.folder());
+            let path = format!("{folder}/{hash}.{ext}");
+
+            let result: Result<(), Error> = async {
+                let bytes = client
+                    .get(kind.cdn_url(user_id, &hash))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await?;
+
+                tokio::fs::create_dir_all(&folder).await?;
+                tokio::fs::write(&path, bytes).await?;
+                Ok(())
+            }
+            .await;
+
+            if let Err(e) = result {
+                println!("Failed to archive {path}: {e}");
+            }
+        });
+    }
+}
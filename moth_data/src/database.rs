@@ -11,7 +11,9 @@ use std::{
     env,
 };
 
+use crate::avatars::Avatars;
 use crate::structs::{DmActivity, Error, Names};
+use moth_config::FilterLists;
 
 use poise::serenity_prelude as serenity;
 
@@ -93,10 +95,25 @@ pub async fn init_data() -> Database {
         banned_users,
         starboard: Mutex::new(StarboardHandler::default()),
         dm_activity: DashMap::new(),
-        names: parking_lot::Mutex::new(Names::new()),
+        names: Names::new(
+            cache_capacity("NAME_CACHE_CAPACITY", 10_000),
+            cache_capacity("NICKNAME_CACHE_CAPACITY", 20_000),
+        ),
+        avatars: Avatars::new(
+            cache_capacity("AVATAR_CACHE_CAPACITY", 10_000),
+            cache_capacity("GUILD_AVATAR_CACHE_CAPACITY", 20_000),
+        ),
     }
 }
 
+/// Reads a cache capacity from the environment, falling back to the default.
+fn cache_capacity(var_name: &str, default: usize) -> usize {
+    env::var(var_name)
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(default)
+}
+
 /// Custom type.
 #[derive(Debug, Clone, sqlx::Type)]
 #[sqlx(type_name = "emoteusagetype")]
@@ -112,6 +129,68 @@ impl PgHasArrayType for EmoteUsageType {
     }
 }
 
+/// Streaming and camera time tracked alongside voice sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "voiceactivitykind")]
+pub enum VoiceActivityKind {
+    Stream,
+    Video,
+}
+
+/// How a member joined a guild, as far as can be told from invite use counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "joinmethod")]
+pub enum JoinMethod {
+    Invite,
+    Vanity,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "filterlist")]
+pub enum FilterList {
+    Bad,
+    Fix,
+}
+
+/// What was done with a member that joined during a raid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "suspectaction")]
+pub enum SuspectAction {
+    None,
+    TimedOut,
+    TimeoutFailed,
+    Kicked,
+    KickFailed,
+}
+
+impl SuspectAction {
+    #[must_use]
+    pub fn describe(self) -> &'static str {
+        match self {
+            SuspectAction::None => "None",
+            SuspectAction::TimedOut => "Timed out",
+            SuspectAction::TimeoutFailed => "Failed to time out",
+            SuspectAction::Kicked => "Kicked",
+            SuspectAction::KickFailed => "Failed to kick",
+        }
+    }
+}
+
+/// What was done about a message with an invite or a message the filter flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "messageaction")]
+pub enum MessageAction {
+    /// Only for invites, which are allowed where they were posted.
+    Allowed,
+    /// Only for invites, which were expired or invalid.
+    Invalid,
+    Log,
+    Delete,
+    Warn,
+    DeleteAndTimeout,
+}
+
 pub struct Database {
     pub db: PgPool,
     banned_users: DashSet<UserId>,
@@ -121,7 +200,8 @@ pub struct Database {
 
     /// Runtime caches for dm activity.
     pub(crate) dm_activity: DashMap<UserId, DmActivity>,
-    pub(crate) names: parking_lot::Mutex<Names>,
+    pub(crate) names: Names,
+    pub(crate) avatars: Avatars,
 }
 
 #[derive(Default, Debug)]
@@ -626,6 +706,94 @@ impl Database {
         Ok(guard.messages.clone())
     }
 
+    /// Loads the filter word lists of every guild.
+    pub async fn get_filter_lists(&self) -> Result<HashMap<serenity::GuildId, FilterLists>, Error> {
+        let records =
+            query!(r#"SELECT guild_id, list AS "list!: FilterList", word FROM filter_words"#)
+                .fetch_all(&self.db)
+                .await?;
+
+        let mut lists: HashMap<serenity::GuildId, FilterLists> = HashMap::new();
+        for record in records {
+            let guild_lists = lists
+                .entry(serenity::GuildId::new(record.guild_id as u64))
+                .or_default();
+            match record.list {
+                FilterList::Bad => guild_lists.badlist.insert(record.word),
+                FilterList::Fix => guild_lists.fixlist.insert(record.word),
+            };
+        }
+
+        Ok(lists)
+    }
+
+    /// Loads the filter word lists of a single guild.
+    pub async fn get_guild_filter_lists(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<FilterLists, Error> {
+        let records = query!(
+            r#"SELECT list AS "list!: FilterList", word FROM filter_words WHERE guild_id = $1"#,
+            guild_id.get() as i64
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut lists = FilterLists::default();
+        for record in records {
+            match record.list {
+                FilterList::Bad => lists.badlist.insert(record.word),
+                FilterList::Fix => lists.fixlist.insert(record.word),
+            };
+        }
+
+        Ok(lists)
+    }
+
+    /// Adds words to a guilds filter list, returning how many weren't already on it.
+    pub async fn add_filter_words(
+        &self,
+        guild_id: serenity::GuildId,
+        list: FilterList,
+        words: &[String],
+        added_by: UserId,
+    ) -> Result<u64, Error> {
+        self.insert_guild(guild_id).await?;
+
+        let result = query!(
+            "INSERT INTO filter_words (guild_id, list, word, added_by, added_at) SELECT $1, $2, \
+             word, $4, $5 FROM UNNEST($3::TEXT[]) AS w(word) ON CONFLICT DO NOTHING",
+            guild_id.get() as i64,
+            list as FilterList,
+            words,
+            added_by.get() as i64,
+            chrono::Utc::now().naive_utc()
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Removes words from a guilds filter list, returning how many were on it.
+    pub async fn remove_filter_words(
+        &self,
+        guild_id: serenity::GuildId,
+        list: FilterList,
+        words: &[String],
+    ) -> Result<u64, Error> {
+        let result = query!(
+            "DELETE FROM filter_words WHERE guild_id = $1 AND list = $2 AND word = ANY($3)",
+            guild_id.get() as i64,
+            list as FilterList,
+            words
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     // temporary function to give access to the inner command overwrites while i figure something out.
     #[must_use]
     pub fn inner_overwrites(&self) -> &Checks {
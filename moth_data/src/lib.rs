@@ -9,6 +9,8 @@
     clippy::cast_sign_loss
 )]
 
+pub mod avatars;
 pub mod database;
 pub mod lob;
+pub mod lru;
 pub mod structs;
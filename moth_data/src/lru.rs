@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, RandomState};
+
+use parking_lot::Mutex;
+
+/// The amount of independently locked shards in a [`ShardedLru`].
+const SHARDS: usize = 16;
+
+/// Marks the lack of a neighbour in the linked list.
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+/// A single least recently used cache, nodes live in a `Vec` and link to each other
+/// by index so promoting and evicting never allocates.
+struct Shard<K, V> {
+    map: HashMap<K, usize>,
+    nodes: Vec<Node<K, V>>,
+    /// Most recently used.
+    head: usize,
+    /// Least recently used, the next to be evicted.
+    tail: usize,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> Shard<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            capacity,
+        }
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.nodes[index].prev, self.nodes[index].next);
+
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.nodes[next].prev = prev;
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.nodes[index].prev = NIL;
+        self.nodes[index].next = self.head;
+
+        if self.head != NIL {
+            self.nodes[self.head].prev = index;
+        }
+        self.head = index;
+
+        if self.tail == NIL {
+            self.tail = index;
+        }
+    }
+
+    fn promote(&mut self, index: usize) {
+        if self.head != index {
+            self.unlink(index);
+            self.push_front(index);
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let index = *self.map.get(key)?;
+        self.promote(index);
+        Some(&self.nodes[index].value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&index) = self.map.get(&key) {
+            self.promote(index);
+            return Some(std::mem::replace(&mut self.nodes[index].value, value));
+        }
+
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let index = if self.nodes.len() < self.capacity {
+            self.nodes.push(Node {
+                key: key.clone(),
+                value,
+                prev: NIL,
+                next: NIL,
+            });
+            self.nodes.len() - 1
+        } else {
+            // reuse the slot of the least recently used entry.
+            let index = self.tail;
+            self.unlink(index);
+
+            let node = &mut self.nodes[index];
+            self.map.remove(&node.key);
+            node.key = key.clone();
+            node.value = value;
+            index
+        };
+
+        self.map.insert(key, index);
+        self.push_front(index);
+        None
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        let Some(index) = self.map.remove(key) else {
+            return false;
+        };
+
+        self.unlink(index);
+
+        // keep the nodes contiguous by moving the last node into the freed slot.
+        let last = self.nodes.len() - 1;
+        if index != last {
+            let (prev, next) = (self.nodes[last].prev, self.nodes[last].next);
+
+            if prev == NIL {
+                self.head = index;
+            } else {
+                self.nodes[prev].next = index;
+            }
+
+            if next == NIL {
+                self.tail = index;
+            } else {
+                self.nodes[next].prev = index;
+            }
+
+            *self.map.get_mut(&self.nodes[last].key).unwrap() = index;
+        }
+
+        self.nodes.swap_remove(index);
+        true
+    }
+}
+
+/// A hash indexed least recently used cache split into independently locked shards.
+///
+/// Every operation is O(1) and only locks the shard the key hashes to, so concurrent
+/// events for different keys rarely contend.
+pub struct ShardedLru<K, V> {
+    shards: Box<[Mutex<Shard<K, V>>]>,
+    hasher: RandomState,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> ShardedLru<K, V> {
+    /// Creates a cache that holds roughly `capacity` entries across all shards.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let per_shard = capacity.div_ceil(SHARDS);
+
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| Mutex::new(Shard::new(per_shard)))
+                .collect(),
+            hasher: RandomState::new(),
+            capacity,
+        }
+    }
+
+    // the modulo keeps the result within the shard count, so truncation is fine.
+    #[allow(clippy::cast_possible_truncation)]
+    fn shard(&self, key: &K) -> &Mutex<Shard<K, V>> {
+        let hash = self.hasher.hash_one(key);
+        &self.shards[(hash as usize) % self.shards.len()]
+    }
+
+    /// Gets a copy of the value, marking it as recently used.
+    #[must_use]
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.shard(key).lock().get(key).cloned()
+    }
+
+    /// Inserts the value, returning the previous value for this key if it was cached.
+    ///
+    /// If the shard is full, the least recently used entry in it is evicted.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).lock().insert(key, value)
+    }
+
+    /// Removes the key from the cache, returning if it was present.
+    pub fn remove(&self, key: &K) -> bool {
+        self.shard(key).lock().remove(key)
+    }
+
+    /// The amount of entries currently cached.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.lock().nodes.len()).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The configured capacity of the cache.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Shard, NIL};
+
+    /// Walks the list from most to least recently used, checking the links agree both ways and
+    /// with the map.
+    fn order(shard: &Shard<u32, u32>) -> Vec<u32> {
+        let mut keys = Vec::new();
+        let mut prev = NIL;
+        let mut index = shard.head;
+
+        while index != NIL {
+            let node = &shard.nodes[index];
+            assert_eq!(node.prev, prev, "broken back link at {index}");
+            assert_eq!(shard.map[&node.key], index, "map is stale for {}", node.key);
+            keys.push(node.key);
+            prev = index;
+            index = node.next;
+        }
+
+        assert_eq!(shard.tail, prev);
+        assert_eq!(keys.len(), shard.nodes.len());
+        assert_eq!(keys.len(), shard.map.len());
+        keys
+    }
+
+    fn filled(keys: &[u32]) -> Shard<u32, u32> {
+        let mut shard = Shard::new(keys.len());
+        for &key in keys {
+            shard.insert(key, key * 10);
+        }
+        shard
+    }
+
+    #[test]
+    fn remove_last_node() {
+        let mut shard = filled(&[1, 2, 3]);
+
+        // 3 is both the last node and the head.
+        assert!(shard.remove(&3));
+        assert_eq!(order(&shard), [2, 1]);
+
+        assert!(shard.remove(&1));
+        assert_eq!(order(&shard), [2]);
+
+        assert!(shard.remove(&2));
+        assert_eq!(order(&shard), []);
+        assert_eq!((shard.head, shard.tail), (NIL, NIL));
+    }
+
+    #[test]
+    fn remove_in_the_middle() {
+        let mut shard = filled(&[1, 2, 3, 4]);
+
+        // 2 sits in the middle of both the list and the nodes, so 4 is moved into its slot.
+        assert!(shard.remove(&2));
+        assert_eq!(order(&shard), [4, 3, 1]);
+        assert_eq!(shard.get(&4), Some(&40));
+        assert_eq!(order(&shard), [4, 3, 1]);
+
+        // the tail is the first node, so the moved node has to become the tail.
+        assert!(shard.remove(&1));
+        assert_eq!(order(&shard), [4, 3]);
+
+        assert!(!shard.remove(&1));
+        shard.insert(5, 50);
+        assert_eq!(order(&shard), [5, 4, 3]);
+        assert_eq!(shard.get(&3), Some(&30));
+        assert_eq!(order(&shard), [3, 5, 4]);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut shard = filled(&[1, 2, 3]);
+
+        // using 1 leaves 2 as the least recently used.
+        assert_eq!(shard.get(&1), Some(&10));
+        assert_eq!(shard.insert(4, 40), None);
+        assert_eq!(order(&shard), [4, 1, 3]);
+        assert_eq!(shard.get(&2), None);
+
+        // updating an entry also counts as using it.
+        assert_eq!(shard.insert(3, 31), Some(30));
+        assert_eq!(shard.insert(5, 50), None);
+        assert_eq!(order(&shard), [5, 3, 4]);
+        assert_eq!(shard.get(&3), Some(&31));
+
+        // eviction still works after a removal freed a slot.
+        assert!(shard.remove(&5));
+        shard.insert(6, 60);
+        shard.insert(7, 70);
+        assert_eq!(order(&shard), [7, 6, 3]);
+    }
+
+    #[test]
+    fn zero_capacity_holds_nothing() {
+        let mut shard = Shard::new(0);
+        assert_eq!(shard.insert(1, 10), None);
+        assert_eq!(order(&shard), []);
+    }
+}
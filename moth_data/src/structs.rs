@@ -1,12 +1,19 @@
 use dashmap::DashMap;
-use parking_lot::RwLock;
-use std::{collections::HashMap, time::Instant};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
 
 use chrono::{NaiveDateTime, Utc};
-use poise::serenity_prelude::{ChannelId, GuildId, MessageId, User, UserId};
+use poise::serenity_prelude::{
+    ChannelId, EmojiId, GuildId, Member, MessageId, StickerId, User, UserId,
+};
 use sqlx::query;
 
-use std::{collections::VecDeque, sync::atomic::AtomicBool};
+use std::sync::atomic::AtomicBool;
+
+use crate::lru::ShardedLru;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Context<'a> = poise::Context<'a, Data, Error>;
@@ -25,6 +32,18 @@ pub struct Data {
     pub config: RwLock<moth_config::MothConfig>,
     /// Experimental anti mass message deletion tracking.
     pub anti_delete_cache: AntiDeleteCache,
+    /// The last known emojis and stickers of each guild, for diffing their update events.
+    pub expression_snapshots: ExpressionSnapshots,
+    /// Voice channel status updates and audit log entries waiting to be matched up.
+    pub vc_status_correlation: VcStatusCorrelation,
+    /// Server mutes and deafens and audit log entries waiting to be matched up.
+    pub voice_moderation_correlation: VoiceModerationCorrelation,
+    /// The use counts of every invite in guilds with invite tracking.
+    pub invite_uses: DashMap<GuildId, HashMap<String, InviteUses>>,
+    /// Recent joins of each guild with raid detection.
+    pub raid_cache: RaidCache,
+    /// Recent message fingerprints of each guild with spam detection.
+    pub spam_cache: SpamCache,
     pub starboard_config: StarboardConfig,
 }
 
@@ -46,10 +65,28 @@ pub struct StarboardConfig {
     pub threshold: u8,
 }
 
-#[derive(Clone, Default, Debug)]
+/// Caches of the latest known names, used to avoid hitting the database on every event.
 pub struct Names {
-    pub usernames: VecDeque<(UserId, UserNames)>,
-    pub nicknames: HashMap<GuildId, VecDeque<(UserId, Option<String>)>>,
+    pub usernames: ShardedLru<UserId, UserNames>,
+    pub nicknames: ShardedLru<(GuildId, UserId), Option<String>>,
+}
+
+/// The outcome of [`Data::bulk_sync_names`].
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NameSyncReport {
+    /// The amount of members compared against the database.
+    pub checked: usize,
+    pub usernames: u64,
+    pub global_names: u64,
+    pub nicknames: u64,
+}
+
+impl NameSyncReport {
+    /// The total amount of names inserted.
+    #[must_use]
+    pub fn changed(&self) -> u64 {
+        self.usernames + self.global_names + self.nicknames
+    }
 }
 
 // I feel like doing it this way instead of a tuple has better representation.
@@ -71,8 +108,11 @@ impl UserNames {
 
 impl Names {
     #[must_use]
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(usernames_capacity: usize, nicknames_capacity: usize) -> Self {
+        Names {
+            usernames: ShardedLru::new(usernames_capacity),
+            nicknames: ShardedLru::new(nicknames_capacity),
+        }
     }
 }
 
@@ -96,210 +136,521 @@ impl DmActivity {
 
 #[derive(Default)]
 pub struct AntiDeleteCache {
-    pub val: DashMap<GuildId, Decay>,
-    // Dashmap using guild key, containing the last deleted msg and a hashmap of stored message ids.
-    pub map: DashMap<GuildId, InnerCache>,
+    /// The current burst of deletions in each guild.
+    pub bursts: DashMap<GuildId, DeleteBurst>,
+    /// Deletions attributed through the audit log, kept apart from the bursts as the entries can
+    /// arrive before the deletions that heat a burst up.
+    pub attributions: DashMap<GuildId, Vec<DeleteAttribution>>,
 }
-pub struct InnerCache {
-    pub last_deleted_msg: MessageId,
-    pub msg_user_cache: HashMap<MessageId, UserId>,
+
+/// Deletions of one moderator or bot in a channel, from a single audit log entry.
+pub struct DeleteAttribution {
+    pub deleter: UserId,
+    pub channel_id: Option<ChannelId>,
+    pub count: u32,
+    pub received: Instant,
 }
-pub struct Decay {
-    pub val: u16,
+
+/// A run of message deletions in a guild, kept until its heat decays to 0.
+pub struct DeleteBurst {
+    pub heat: u16,
     pub last_update: Instant,
+    /// If an alert was sent for this burst, only alerted bursts get a summary.
+    pub alerted: bool,
+    pub last_deleted_msg: Option<MessageId>,
+    /// Authors of messages fetched over http, used when the author isn't stored anywhere.
+    pub msg_user_cache: HashMap<MessageId, UserId>,
+    pub channels: HashMap<ChannelId, u32>,
+    pub authors: HashMap<UserId, u32>,
+    /// Moderators and bots attributed through the audit log, with the channel they deleted in.
+    pub deleters: HashMap<(UserId, Option<ChannelId>), u32>,
+}
+
+impl DeleteBurst {
+    #[must_use]
+    pub fn new() -> Self {
+        DeleteBurst {
+            heat: 0,
+            last_update: Instant::now(),
+            alerted: false,
+            last_deleted_msg: None,
+            msg_user_cache: HashMap::new(),
+            channels: HashMap::new(),
+            authors: HashMap::new(),
+            deleters: HashMap::new(),
+        }
+    }
+}
+
+impl Default for DeleteBurst {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AntiDeleteCache {
-    /// Check if all values should be decayed and if so, decay them.
-    pub fn decay_proc(&self) {
-        let now = Instant::now();
+    /// Decays the heat of every burst that has been idle for longer than its guilds decay time,
+    /// returning the bursts that cooled down completely along with their attributions.
+    ///
+    /// Attributions in guilds without a burst are dropped once they are older than the decay time.
+    pub fn decay_proc(
+        &self,
+        decay_after: impl Fn(GuildId) -> std::time::Duration,
+    ) -> Vec<(GuildId, DeleteBurst)> {
         let mut to_remove = vec![];
-        for mut entry in self.val.iter_mut() {
-            let guild = entry.value_mut();
-            let elapsed = now.duration_since(guild.last_update).as_secs();
-            // time without messages deleted to decay, hardcoded currently.
-            if elapsed > 5 {
-                guild.val -= 1;
+        for mut entry in self.bursts.iter_mut() {
+            let guild_id = *entry.key();
+            let burst = entry.value_mut();
+            if burst.last_update.elapsed() > decay_after(guild_id) {
+                burst.heat = burst.heat.saturating_sub(1);
             }
-            if guild.val == 0 {
-                to_remove.push(*entry.key());
+            if burst.heat == 0 {
+                to_remove.push(guild_id);
             }
         }
-        for entry in to_remove {
-            self.val.remove(&entry);
+
+        let finished = to_remove
+            .into_iter()
+            .filter_map(|guild_id| self.bursts.remove(&guild_id))
+            .map(|(guild_id, mut burst)| {
+                if let Some((_, attributions)) = self.attributions.remove(&guild_id) {
+                    for attribution in attributions {
+                        *burst
+                            .deleters
+                            .entry((attribution.deleter, attribution.channel_id))
+                            .or_default() += attribution.count;
+                    }
+                }
+                (guild_id, burst)
+            })
+            .collect();
+
+        self.attributions.retain(|guild_id, attributions| {
+            if self.bursts.contains_key(guild_id) {
+                return true;
+            }
+            let decay_after = decay_after(*guild_id);
+            attributions.retain(|a| a.received.elapsed() <= decay_after);
+            !attributions.is_empty()
+        });
+
+        finished
+    }
+}
+
+#[derive(Default)]
+pub struct RaidCache {
+    /// The current burst of joins in each guild.
+    pub bursts: DashMap<GuildId, JoinBurst>,
+}
+
+/// A run of joins in a guild, kept until its heat decays to 0.
+pub struct JoinBurst {
+    pub heat: u32,
+    pub last_update: Instant,
+    pub joins: Vec<SuspectJoin>,
+    /// If the burst was treated as a raid, set before the raid is stored and reset if storing it
+    /// failed.
+    pub raided: bool,
+    /// The verification level before it was raised, kept in case storing the raid fails.
+    pub previous_verification: Option<i16>,
+    /// The database id of the raid, joins after this is set are responded to on their own.
+    pub raid_id: Option<i32>,
+}
+
+impl JoinBurst {
+    #[must_use]
+    pub fn new() -> Self {
+        JoinBurst {
+            heat: 0,
+            last_update: Instant::now(),
+            joins: Vec::new(),
+            raided: false,
+            previous_verification: None,
+            raid_id: None,
         }
     }
 }
 
-#[allow(clippy::missing_panics_doc)]
-impl Data {
-    pub async fn check_or_insert_user(&self, user: &User) {
-        // this logic is barebones and should probably use drain or something else?
-        // i don't plan on changing the limit at runtime so the current implementation should be fine.
-        const MAX_LENGTH: usize = 250;
+impl Default for JoinBurst {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
+pub struct SuspectJoin {
+    pub user_id: UserId,
+    pub name: String,
+    pub joined_at: NaiveDateTime,
+    pub new_account: bool,
+    pub default_avatar: bool,
+    pub similar_name: bool,
+}
+
+impl SuspectJoin {
+    /// Every join is worth 1, with 1 more for each signal.
+    #[must_use]
+    pub fn score(&self) -> u32 {
+        1 + u32::from(self.new_account)
+            + u32::from(self.default_avatar)
+            + u32::from(self.similar_name)
+    }
+
+    #[must_use]
+    pub fn signals(&self) -> String {
+        let signals = [
+            (self.new_account, "new account"),
+            (self.default_avatar, "default avatar"),
+            (self.similar_name, "similar name"),
+        ];
+
+        signals
+            .iter()
+            .filter(|(present, _)| *present)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
 
-        // Iterate through the cached names and check if the user is present.
-        // If the user is present, move them to the back, updating the value if needed.
+impl RaidCache {
+    /// Halves the heat of every burst that has been idle for longer than its guilds decay time,
+    /// returning the bursts that cooled down completely.
+    ///
+    /// Raids can build up far more heat than the threshold, so it is halved rather than lowered
+    /// by 1 to end them shortly after the joins stop.
+    pub fn decay_proc(
+        &self,
+        decay_after: impl Fn(GuildId) -> std::time::Duration,
+    ) -> Vec<(GuildId, JoinBurst)> {
+        let mut to_remove = vec![];
+        for mut entry in self.bursts.iter_mut() {
+            let guild_id = *entry.key();
+            let burst = entry.value_mut();
+            if burst.last_update.elapsed() > decay_after(guild_id) {
+                burst.heat /= 2;
+            }
+            if burst.heat == 0 {
+                to_remove.push(guild_id);
+            }
+        }
+
+        to_remove
+            .into_iter()
+            .filter_map(|guild_id| self.bursts.remove(&guild_id))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct SpamCache {
+    pub guilds: DashMap<GuildId, SpamWindow>,
+}
+
+#[derive(Default)]
+pub struct SpamWindow {
+    pub messages: VecDeque<SeenMessage>,
+    /// Fingerprints already alerted on, with the user for repeats and `None` for copies across
+    /// several users.
+    pub fired: HashMap<(u64, Option<UserId>), Instant>,
+}
 
-        let mut update_user = false;
-        let mut update_display = false;
-        let mut check_db = false;
+pub struct SeenMessage {
+    pub fingerprint: u64,
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub seen: Instant,
+}
 
+impl SpamWindow {
+    /// Forgets messages and alerts older than the window.
+    pub fn prune(&mut self, window: std::time::Duration) {
+        while self
+            .messages
+            .front()
+            .is_some_and(|m| m.seen.elapsed() > window)
         {
-            let names = &mut self.database.names.lock();
-            let usernames = &mut names.usernames;
+            self.messages.pop_front();
+        }
 
-            if let Some(index) = usernames.iter().position(|(id, _)| id.eq(&user.id)) {
-                // remove so the user can be moved later.
-                let (_, cached_name) = usernames.remove(index).unwrap();
+        self.fired.retain(|_, fired| fired.elapsed() <= window);
+    }
+}
 
-                // Update the user in the database if the username is different.
-                update_user = !cached_name.username.eq(&user.tag());
+/// Emoji and sticker update events only contain the new state, so the old names are kept here.
+#[derive(Default)]
+pub struct ExpressionSnapshots {
+    pub emojis: DashMap<GuildId, HashMap<EmojiId, String>>,
+    pub stickers: DashMap<GuildId, HashMap<StickerId, String>>,
+}
 
-                let global_name = user
-                    .global_name
-                    .as_ref()
-                    .map(std::string::ToString::to_string);
+#[derive(Clone, Debug)]
+pub struct InviteUses {
+    pub inviter: Option<UserId>,
+    pub uses: u64,
+    /// 0 if the invite can be used any number of times.
+    pub max_uses: u64,
+}
 
-                update_display = cached_name.global_name.eq(&global_name);
+/// Voice channel status updates and their audit log entries arrive as separate events in no
+/// particular order, so whichever comes first waits here for the other.
+///
+/// Keyed by the channel and the new status, with cleared statuses as "".
+#[derive(Default)]
+pub struct VcStatusCorrelation {
+    pending: Mutex<HashMap<(ChannelId, String), VcStatusHalf>>,
+}
 
-                if let Some(global) = &user.global_name {
-                    // only update this if they have a new display name, also use old name if new is none.
-                    update_display = cached_name.global_name.eq(&Some(global.to_string()));
-                };
+enum VcStatusHalf {
+    Status(PendingVcStatus),
+    Author(UserId, Instant),
+}
 
-                usernames.push_back((user.id, UserNames::new(user.tag(), global_name)));
+pub struct PendingVcStatus {
+    /// The id of the row in `vc_status_history`.
+    pub history_id: i32,
+    pub guild_id: GuildId,
+    pub old: Option<String>,
+    pub received: Instant,
+}
 
-                // Length will not be configurable at this time so this doesn't need to do anything fancy.
-                if usernames.len() > MAX_LENGTH {
-                    usernames.pop_front();
-                }
-            } else {
-                check_db = true;
-            }
+impl VcStatusCorrelation {
+    /// How long either side waits for the other before being dropped.
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+    /// Returns the status with its author if the audit log entry already arrived.
+    pub fn status_received(
+        &self,
+        key: (ChannelId, String),
+        status: PendingVcStatus,
+    ) -> Option<(PendingVcStatus, UserId)> {
+        let mut pending = self.pending.lock();
+        if let Some(VcStatusHalf::Author(user_id, _)) = pending.remove(&key) {
+            return Some((status, user_id));
         }
 
-        match (update_user, update_display) {
-            (true, true) => {
-                let _ = self.insert_user_db(user.id, user.tag()).await;
-                let _ = self
-                    .insert_display_db(user.id, user.global_name.clone().map(|s| s.to_string()))
-                    .await;
-                return;
-            }
-            (true, false) => {
-                let _ = self.insert_user_db(user.id, user.tag()).await;
-                return;
-            }
-            (false, true) => {
-                let _ = self
-                    .insert_display_db(user.id, user.global_name.clone().map(|s| s.to_string()))
-                    .await;
-                return;
-            }
-            (false, false) => (),
+        pending.insert(key, VcStatusHalf::Status(status));
+        None
+    }
+
+    /// Returns the status this audit log entry was for, if it already arrived.
+    pub fn author_received(
+        &self,
+        key: (ChannelId, String),
+        user_id: UserId,
+    ) -> Option<(PendingVcStatus, UserId)> {
+        let mut pending = self.pending.lock();
+        if let Some(VcStatusHalf::Status(status)) = pending.remove(&key) {
+            return Some((status, user_id));
         }
 
-        if check_db {
-            if let Some(db_name) = self.get_latest_username_psql(user.id).await {
-                if !db_name.eq(&user.tag()) {
-                    let _ = self.insert_user_db(user.id, user.tag()).await;
-                }
-            } else {
-                let _ = self.insert_user_db(user.id, user.tag()).await;
-            }
+        pending.insert(key, VcStatusHalf::Author(user_id, Instant::now()));
+        None
+    }
 
-            if let Some(db_name) = self.get_latest_global_name_psql(user.id).await {
-                // never insert if no name.
-                if let Some(user_global_name) = &user.global_name {
-                    if !db_name.eq(user_global_name) {
-                        let _ = self
-                            .insert_display_db(
-                                user.id,
-                                user.global_name.clone().map(|s| s.to_string()),
-                            )
-                            .await;
-                    }
-                }
-            } else {
-                // optional values are handled internally on this function.
-                let _ = self
-                    .insert_display_db(user.id, user.global_name.clone().map(|s| s.to_string()))
-                    .await;
-            }
+    /// Drops anything that was never matched, statuses stay unattributed in the history.
+    pub fn prune(&self) {
+        self.pending.lock().retain(|_, half| {
+            let received = match half {
+                VcStatusHalf::Status(status) => status.received,
+                VcStatusHalf::Author(_, received) => *received,
+            };
+            received.elapsed() < Self::TIMEOUT
+        });
+    }
+}
 
-            // cache the names.
-            let usernames = &mut self.database.names.lock().usernames;
-            usernames.push_back((
-                user.id,
-                UserNames::new(user.tag(), user.global_name.clone().map(|s| s.to_string())),
-            ));
+/// A server mute or deafen being applied (true) or lifted (false).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoiceModeration {
+    Mute(bool),
+    Deaf(bool),
+}
 
-            if usernames.len() > MAX_LENGTH {
-                usernames.pop_front();
-            }
+impl VoiceModeration {
+    #[must_use]
+    pub fn describe(self) -> &'static str {
+        match self {
+            VoiceModeration::Mute(true) => "server muted",
+            VoiceModeration::Mute(false) => "server unmuted",
+            VoiceModeration::Deaf(true) => "server deafened",
+            VoiceModeration::Deaf(false) => "server undeafened",
         }
     }
+}
 
-    pub async fn check_or_insert_nick(
+/// Voice state updates and the audit log entries of the moderator responsible arrive as separate
+/// events in no particular order, so whichever comes first waits here for the other.
+///
+/// Keyed by the guild, the member and what was done to them.
+#[derive(Default)]
+pub struct VoiceModerationCorrelation {
+    pending: Mutex<HashMap<(GuildId, UserId, VoiceModeration), VoiceModerationHalf>>,
+}
+
+enum VoiceModerationHalf {
+    /// The channel the member was in.
+    State(ChannelId, Instant),
+    Moderator(UserId, Instant),
+}
+
+impl VoiceModerationCorrelation {
+    /// How long either side waits for the other before being dropped.
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+    /// Returns the moderator responsible if their audit log entry already arrived.
+    pub fn state_received(
         &self,
-        guild_id: GuildId,
-        user_id: UserId,
-        nick: Option<String>,
-    ) {
-        const MAX_LENGTH: usize = 250;
+        key: (GuildId, UserId, VoiceModeration),
+        channel_id: ChannelId,
+    ) -> Option<UserId> {
+        let mut pending = self.pending.lock();
+        if let Some(VoiceModerationHalf::Moderator(moderator_id, _)) = pending.remove(&key) {
+            return Some(moderator_id);
+        }
 
-        let mut update_user = false;
-        let mut check_db = false;
+        pending.insert(key, VoiceModerationHalf::State(channel_id, Instant::now()));
+        None
+    }
 
-        {
-            let names = &mut self.database.names.lock();
-            let nicknames = names.nicknames.entry(guild_id).or_default();
+    /// Returns the channel the member was in if their voice state update already arrived.
+    pub fn moderator_received(
+        &self,
+        key: (GuildId, UserId, VoiceModeration),
+        moderator_id: UserId,
+    ) -> Option<ChannelId> {
+        let mut pending = self.pending.lock();
+        if let Some(VoiceModerationHalf::State(channel_id, _)) = pending.remove(&key) {
+            return Some(channel_id);
+        }
 
-            if let Some(index) = nicknames.iter().position(|(id, _)| id.eq(&user_id)) {
-                let (_, cached_name) = nicknames.remove(index).unwrap();
+        pending.insert(
+            key,
+            VoiceModerationHalf::Moderator(moderator_id, Instant::now()),
+        );
+        None
+    }
 
-                // Update the nickname in database if different.
-                update_user = !cached_name.eq(&nick);
+    /// Drops anything that was never matched.
+    pub fn prune(&self) {
+        self.pending.lock().retain(|_, half| {
+            let received = match half {
+                VoiceModerationHalf::State(_, received)
+                | VoiceModerationHalf::Moderator(_, received) => *received,
+            };
+            received.elapsed() < Self::TIMEOUT
+        });
+    }
+}
 
-                nicknames.push_back((user_id, nick.clone()));
+#[allow(clippy::missing_panics_doc)]
+impl Data {
+    /// Reloads the filter word lists of a guild after they were changed.
+    pub async fn refresh_filter_lists(&self, guild_id: GuildId) -> Result<(), Error> {
+        let lists = self.database.get_guild_filter_lists(guild_id).await?;
+        self.config.write().events.set_filter_lists(guild_id, lists);
 
-                // Length will not be configurable at this time so this doesn't need to do anything fancy.
-                if nicknames.len() > MAX_LENGTH {
-                    nicknames.pop_front();
-                }
-            } else {
-                check_db = true;
+        Ok(())
+    }
+
+    /// The cached nickname of a member, `None` if it isn't cached.
+    #[must_use]
+    pub fn cached_nick(&self, guild_id: GuildId, user_id: UserId) -> Option<Option<String>> {
+        self.database.names.nicknames.get(&(guild_id, user_id))
+    }
+
+    /// The cached global name of a user, `None` if it isn't cached.
+    #[must_use]
+    pub fn cached_global_name(&self, user_id: UserId) -> Option<Option<String>> {
+        self.database
+            .names
+            .usernames
+            .get(&user_id)
+            .map(|n| n.global_name)
+    }
+
+    pub async fn check_or_insert_user(&self, user: &User) {
+        let global_name = user
+            .global_name
+            .as_ref()
+            .map(std::string::ToString::to_string);
+
+        // Swap in the latest names, the old value tells us if anything changed.
+        let cached = self
+            .database
+            .names
+            .usernames
+            .insert(user.id, UserNames::new(user.tag(), global_name.clone()));
+
+        if let Some(cached_name) = cached {
+            // Update the user in the database if the username is different.
+            if cached_name.username != user.tag() {
+                let _ = self.insert_user_db(user.id, user.tag()).await;
+            }
+
+            // only update this if they have a new display name, keep the old name if new is none.
+            if global_name.is_some() && cached_name.global_name != global_name {
+                let _ = self.insert_display_db(user.id, global_name).await;
             }
-        }
 
-        if update_user {
-            let _ = self.insert_nick_db(guild_id, user_id, nick).await;
             return;
         }
 
-        if check_db {
-            if let Some(db_name) = self.get_latest_nickname_psql(guild_id, user_id).await {
-                // never insert if no name.
-                if let Some(nick) = nick.clone() {
-                    if !db_name.eq(&nick) {
-                        // optional stuff is handled internally.
-                        let _ = self.insert_nick_db(guild_id, user_id, Some(nick)).await;
-                    }
+        if let Some(db_name) = self.get_latest_username_psql(user.id).await {
+            if !db_name.eq(&user.tag()) {
+                let _ = self.insert_user_db(user.id, user.tag()).await;
+            }
+        } else {
+            let _ = self.insert_user_db(user.id, user.tag()).await;
+        }
+
+        if let Some(db_name) = self.get_latest_global_name_psql(user.id).await {
+            // never insert if no name.
+            if let Some(user_global_name) = &global_name {
+                if !db_name.eq(user_global_name) {
+                    let _ = self.insert_display_db(user.id, global_name).await;
                 }
-            } else {
-                // optional values are handled internally on this function.
-                let _ = self.insert_nick_db(guild_id, user_id, nick.clone()).await;
             }
+        } else {
+            // optional values are handled internally on this function.
+            let _ = self.insert_display_db(user.id, global_name).await;
+        }
+    }
 
-            let names = &mut self.database.names.lock();
-            let nicknames = names.nicknames.entry(guild_id).or_default();
+    pub async fn check_or_insert_nick(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        nick: Option<String>,
+    ) {
+        let cached = self
+            .database
+            .names
+            .nicknames
+            .insert((guild_id, user_id), nick.clone());
+
+        if let Some(cached_name) = cached {
+            // Update the nickname in database if different.
+            if cached_name != nick {
+                let _ = self.insert_nick_db(guild_id, user_id, nick).await;
+            }
 
-            nicknames.push_back((user_id, nick));
+            return;
+        }
 
-            if nicknames.len() > MAX_LENGTH {
-                nicknames.pop_front();
+        if let Some(db_name) = self.get_latest_nickname_psql(guild_id, user_id).await {
+            // never insert if no name.
+            if let Some(nick) = nick {
+                if !db_name.eq(&nick) {
+                    // optional stuff is handled internally.
+                    let _ = self.insert_nick_db(guild_id, user_id, Some(nick)).await;
+                }
             }
+        } else {
+            // optional values are handled internally on this function.
+            let _ = self.insert_nick_db(guild_id, user_id, nick).await;
         }
     }
 
@@ -309,9 +660,11 @@ impl Data {
         self.database.insert_user(user_id).await?;
 
         query!(
-            "INSERT INTO usernames (user_id, username, timestamp) VALUES ($1, $2, $3)",
+            "INSERT INTO usernames (user_id, username, skeleton, timestamp) VALUES ($1, $2, $3, \
+             $4)",
             i64::from(user_id),
             name,
+            moth_filter::skeleton(&name),
             timestamp
         )
         .execute(&self.database.db)
@@ -330,9 +683,11 @@ impl Data {
         self.database.insert_user(user_id).await?;
 
         query!(
-            "INSERT INTO global_names (user_id, global_name, timestamp) VALUES ($1, $2, $3)",
+            "INSERT INTO global_names (user_id, global_name, skeleton, timestamp) VALUES ($1, $2, \
+             $3, $4)",
             i64::from(user_id),
             name,
+            moth_filter::skeleton(&name),
             timestamp
         )
         .execute(&self.database.db)
@@ -357,11 +712,12 @@ impl Data {
         self.database.insert_guild(guild_id).await?;
 
         let _ = query!(
-            "INSERT INTO nicknames (guild_id, user_id, nickname, timestamp) VALUES ($1, $2, $3, \
-             $4)",
+            "INSERT INTO nicknames (guild_id, user_id, nickname, skeleton, timestamp) VALUES ($1, \
+             $2, $3, $4, $5)",
             i64::from(guild_id),
             i64::from(user_id),
             name,
+            moth_filter::skeleton(&name),
             timestamp
         )
         .execute(&self.database.db)
@@ -414,6 +770,203 @@ impl Data {
         }
     }
 
+    /// Syncs the names of many members at once, for guild creates and member chunks.
+    ///
+    /// Every member is diffed against their latest stored names in one query, and only
+    /// the changes are inserted.
+    pub async fn bulk_sync_names<'a>(
+        &self,
+        guild_id: GuildId,
+        members: impl IntoIterator<Item = &'a Member>,
+    ) -> Result<NameSyncReport, Error> {
+        let mut user_ids = Vec::new();
+        let mut usernames = Vec::new();
+        let mut global_names = Vec::new();
+        let mut nicknames = Vec::new();
+        let mut cache = Vec::new();
+
+        for member in members {
+            let global_name = member.user.global_name.as_ref().map(ToString::to_string);
+            let nick = member.nick.as_ref().map(ToString::to_string);
+
+            cache.push((
+                member.user.id,
+                UserNames::new(member.user.tag(), global_name.clone()),
+                nick.clone(),
+            ));
+
+            user_ids.push(i64::from(member.user.id));
+            usernames.push(member.user.tag());
+            global_names.push(global_name);
+            nicknames.push(nick);
+        }
+
+        let mut report = NameSyncReport {
+            checked: user_ids.len(),
+            ..Default::default()
+        };
+
+        if user_ids.is_empty() {
+            return Ok(report);
+        }
+
+        let changes = query!(
+            r#"SELECT m.user_id AS "user_id!", m.username AS "username!", m.global_name,
+                   m.nickname,
+                   (u.username IS DISTINCT FROM m.username) AS "username_changed!",
+                   (m.global_name IS NOT NULL AND g.global_name IS DISTINCT FROM m.global_name)
+                       AS "global_name_changed!",
+                   (m.nickname IS NOT NULL AND n.nickname IS DISTINCT FROM m.nickname)
+                       AS "nickname_changed!"
+               FROM UNNEST($1::BIGINT[], $2::TEXT[], $3::TEXT[], $4::TEXT[])
+                   AS m(user_id, username, global_name, nickname)
+               LEFT JOIN LATERAL (
+                   SELECT username FROM usernames WHERE user_id = m.user_id
+                   ORDER BY timestamp DESC LIMIT 1
+               ) u ON TRUE
+               LEFT JOIN LATERAL (
+                   SELECT global_name FROM global_names WHERE user_id = m.user_id
+                   ORDER BY timestamp DESC LIMIT 1
+               ) g ON TRUE
+               LEFT JOIN LATERAL (
+                   SELECT nickname FROM nicknames WHERE guild_id = $5 AND user_id = m.user_id
+                   ORDER BY timestamp DESC LIMIT 1
+               ) n ON TRUE"#,
+            &user_ids,
+            &usernames,
+            &global_names as &[Option<String>],
+            &nicknames as &[Option<String>],
+            i64::from(guild_id)
+        )
+        .fetch_all(&self.database.db)
+        .await?;
+
+        let mut changed_users = Vec::new();
+        let (mut username_ids, mut username_values) = (Vec::new(), Vec::new());
+        let (mut global_ids, mut global_values) = (Vec::new(), Vec::new());
+        let (mut nick_ids, mut nick_values) = (Vec::new(), Vec::new());
+
+        for change in changes {
+            if change.username_changed {
+                username_ids.push(change.user_id);
+                username_values.push(change.username);
+            }
+
+            if change.global_name_changed {
+                if let Some(global_name) = change.global_name {
+                    global_ids.push(change.user_id);
+                    global_values.push(global_name);
+                }
+            }
+
+            if change.nickname_changed {
+                if let Some(nickname) = change.nickname {
+                    nick_ids.push(change.user_id);
+                    nick_values.push(nickname);
+                }
+            }
+
+            if change.username_changed || change.global_name_changed || change.nickname_changed {
+                changed_users.push(change.user_id);
+            }
+        }
+
+        if changed_users.is_empty() {
+            self.cache_synced_names(guild_id, cache);
+            return Ok(report);
+        }
+
+        let username_skeletons = username_values
+            .iter()
+            .map(|n| moth_filter::skeleton(n))
+            .collect::<Vec<_>>();
+        let global_skeletons = global_values
+            .iter()
+            .map(|n| moth_filter::skeleton(n))
+            .collect::<Vec<_>>();
+        let nick_skeletons = nick_values
+            .iter()
+            .map(|n| moth_filter::skeleton(n))
+            .collect::<Vec<_>>();
+
+        let timestamp: NaiveDateTime = Utc::now().naive_utc();
+        let mut transaction = self.database.db.begin().await?;
+
+        query!(
+            "INSERT INTO users (user_id) SELECT * FROM UNNEST($1::BIGINT[]) ON CONFLICT (user_id) \
+             DO NOTHING",
+            &changed_users
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        query!(
+            "INSERT INTO guilds (guild_id) VALUES ($1) ON CONFLICT (guild_id) DO NOTHING",
+            i64::from(guild_id)
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let inserted = query!(
+            r#"WITH new_usernames AS (
+                   INSERT INTO usernames (user_id, username, skeleton, timestamp)
+                   SELECT *, $10::TIMESTAMP FROM UNNEST($1::BIGINT[], $2::TEXT[], $3::TEXT[])
+                   RETURNING 1
+               ), new_global_names AS (
+                   INSERT INTO global_names (user_id, global_name, skeleton, timestamp)
+                   SELECT *, $10::TIMESTAMP FROM UNNEST($4::BIGINT[], $5::TEXT[], $6::TEXT[])
+                   RETURNING 1
+               ), new_nicknames AS (
+                   INSERT INTO nicknames (guild_id, user_id, nickname, skeleton, timestamp)
+                   SELECT $11::BIGINT, *, $10::TIMESTAMP
+                   FROM UNNEST($7::BIGINT[], $8::TEXT[], $9::TEXT[])
+                   RETURNING 1
+               )
+               SELECT (SELECT COUNT(*) FROM new_usernames) AS "usernames!",
+                      (SELECT COUNT(*) FROM new_global_names) AS "global_names!",
+                      (SELECT COUNT(*) FROM new_nicknames) AS "nicknames!""#,
+            &username_ids,
+            &username_values,
+            &username_skeletons,
+            &global_ids,
+            &global_values,
+            &global_skeletons,
+            &nick_ids,
+            &nick_values,
+            &nick_skeletons,
+            timestamp,
+            i64::from(guild_id)
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        self.cache_synced_names(guild_id, cache);
+
+        report.usernames = inserted.usernames as u64;
+        report.global_names = inserted.global_names as u64;
+        report.nicknames = inserted.nicknames as u64;
+
+        Ok(report)
+    }
+
+    /// Warms the name caches with synced names, so the next event from these members is cheap.
+    ///
+    /// Only called once the names are stored, otherwise a failed sync would never be retried.
+    fn cache_synced_names(
+        &self,
+        guild_id: GuildId,
+        names: Vec<(UserId, UserNames, Option<String>)>,
+    ) {
+        for (user_id, user_names, nick) in names {
+            self.database.names.usernames.insert(user_id, user_names);
+            self.database
+                .names
+                .nicknames
+                .insert((guild_id, user_id), nick);
+        }
+    }
+
     pub async fn get_activity_check(&self, user_id: UserId) -> Option<DmActivity> {
         let cached = self.database.dm_activity.get(&user_id);
 
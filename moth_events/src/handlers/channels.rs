@@ -1,21 +1,19 @@
 use crate::helper::{
     auto_archive_duration_to_string, channel_type_to_string, forum_layout_to_string,
-    get_channel_name, get_guild_name_override, get_permission_changes, get_user, overwrite_removal,
+    get_channel_name, get_guild_name_override, get_permission_changes, overwrite_removal,
     sort_order_to_string,
 };
 
+use crate::handlers::name_filter;
 use crate::{Data, Error};
 
 use moth_ansi::{BLUE, HI_BLUE, RESET};
-use poise::serenity_prelude::audit_log::Action::VoiceChannelStatus;
 use poise::serenity_prelude::{
-    self as serenity, ChannelFlags, ChannelId, ChannelType, CreateEmbed, ForumEmoji, GuildChannel,
-    GuildId, PartialGuildChannel, UserId, VoiceChannelStatusAction,
+    self as serenity, ChannelFlags, ChannelType, ForumEmoji, GuildChannel, PartialGuildChannel,
 };
 
 use std::fmt::Write;
 use std::sync::Arc;
-use std::time::Duration;
 
 pub async fn channel_create(
     ctx: &serenity::Context,
@@ -316,6 +314,8 @@ pub async fn thread_create(
         "{HI_BLUE}[{}] Thread #{} ({}) was created in #{}!{RESET}",
         guild_name, thread.name, kind, parent_channel_name
     );
+
+    name_filter::check_thread(ctx, &data, None, thread).await?;
     Ok(())
 }
 
@@ -396,6 +396,7 @@ pub async fn thread_update(
         );
     }
 
+    name_filter::check_thread(ctx, &data, old.as_ref(), new).await?;
     Ok(())
 }
 
@@ -432,216 +433,3 @@ pub async fn thread_delete(
     }
     Ok(())
 }
-
-pub async fn voice_channel_status_update(
-    ctx: &serenity::Context,
-    old: &Option<String>,
-    status: &Option<String>,
-    id: &ChannelId,
-    guild_id: &GuildId,
-    data: Arc<Data>,
-) -> Result<(), Error> {
-    let vcstatus = {
-        let config = data.config.read();
-        config.vcstatus.clone()
-    };
-    if vcstatus.action {
-        let old_field: Option<String>;
-        let new_field: Option<String>;
-        match (old, status.clone()) {
-            (None, None) => {
-                old_field = None;
-                new_field = None;
-                add(ctx, id, guild_id, old_field, new_field, status, &data).await?;
-            }
-            (Some(old), Some(status)) => {
-                old_field = Some(old.to_string());
-                new_field = Some(status.clone());
-                if old_field != new_field {
-                    add(
-                        ctx,
-                        id,
-                        guild_id,
-                        old_field,
-                        new_field,
-                        &Some(status),
-                        &data,
-                    )
-                    .await?;
-                }
-            }
-            (None, Some(status)) => {
-                old_field = None;
-                new_field = Some(status.clone());
-                add(
-                    ctx,
-                    id,
-                    guild_id,
-                    old_field,
-                    new_field,
-                    &Some(status),
-                    &data,
-                )
-                .await?;
-            }
-            _ => {}
-        }
-    }
-    Ok(())
-}
-
-pub async fn add(
-    ctx: &serenity::Context,
-    id: &ChannelId,
-    guild_id: &GuildId,
-    old_field: Option<String>,
-    new_field: Option<String>,
-    status: &Option<String>,
-    data: &Data,
-) -> Result<(), Error> {
-    tokio::time::sleep(Duration::from_secs(2)).await;
-    let logs = guild_id
-        .audit_logs(
-            &ctx.http,
-            Some(VoiceChannelStatus(VoiceChannelStatusAction::StatusUpdate)),
-            None,
-            None,
-            Some(nonmax::NonMaxU8::new(5).unwrap()),
-        )
-        .await?;
-    let mut user_id: Option<UserId> = None;
-
-    for log in &logs.entries {
-        if log.options.is_none() {
-            continue;
-        }
-
-        let options = &log.options.as_ref().unwrap();
-
-        if let Some(str) = &options.status {
-            if str == status.as_deref().unwrap_or_default() && options.channel_id == Some(*id) {
-                user_id = Some(log.user_id.unwrap());
-                break;
-            }
-        }
-    }
-
-    let Some(user_id) = user_id else {
-        return Ok(());
-    };
-
-    let vcstatus = {
-        let config = data.config.read();
-        config.vcstatus.clone()
-    };
-
-    // check if regex for blacklists exist and if a new status exists.
-    let blacklisted = if let (Some(regex_patterns), Some(value)) = (&vcstatus.regex, &new_field) {
-        check_blacklisted(value, regex_patterns).await
-    } else {
-        false
-    };
-
-    post_messages(
-        ctx,
-        data,
-        *id,
-        *guild_id,
-        old_field.as_deref(),
-        new_field.as_deref(),
-        user_id,
-        blacklisted,
-    )
-    .await?;
-    Ok(())
-}
-
-async fn check_blacklisted(msg: &str, patterns: &[regex::Regex]) -> bool {
-    patterns.iter().any(|pattern| pattern.is_match(msg))
-}
-
-// should probably fix the ordering of arguments.
-#[allow(clippy::too_many_arguments)]
-async fn post_messages(
-    ctx: &serenity::Context,
-    data: &Data,
-    channel_id: ChannelId,
-    guild_id: GuildId,
-    old: Option<&str>,
-    new: Option<&str>,
-    user_id: UserId,
-    blacklisted: bool,
-) -> Result<(), Error> {
-    let channel_str: &str = &format!("<#{}>", channel_id.get());
-
-    let old_field = match old {
-        Some(value) if !value.is_empty() => ("Old", value, true),
-        _ => ("Old", "None", true),
-    };
-
-    let new_field = match new {
-        Some(value) if !value.is_empty() => ("New", value, true),
-        _ => ("New", "None", true),
-    };
-
-    let fields = [("Channel", channel_str, true), old_field, new_field];
-
-    // i 100% shouldn't unwrap here but i'm only reducing http requests right now not fixing jank.
-    let user: serenity::User = get_user(ctx, guild_id, user_id).await.unwrap();
-    let author_title = format!("{} changed a channel status", user.name);
-    let author = serenity::CreateEmbedAuthor::new(author_title).icon_url(user.face());
-    let footer = serenity::CreateEmbedFooter::new(format!(
-        "User ID: {} • Please check user manually in audit log.",
-        user.id.get()
-    ));
-
-    let embed = serenity::CreateEmbed::default()
-        .fields(fields)
-        .author(author)
-        .footer(footer);
-
-    send_msgs(ctx, data, user_id, embed, blacklisted).await?;
-
-    Ok(())
-}
-
-async fn send_msgs(
-    ctx: &serenity::Context,
-    data: &Data,
-    user_id: UserId,
-    embed: CreateEmbed<'_>,
-    blacklisted: bool,
-) -> Result<(), Error> {
-    let (post, announce) = {
-        let status = &data.config.read().vcstatus;
-        (status.post_channel, status.announce_channel)
-    };
-
-    let content = if blacklisted {
-        format!("<@{user_id}>: **Blacklisted word in status!**")
-    } else {
-        format!("<@{user_id}>")
-    };
-
-    let mentions = serenity::CreateAllowedMentions::new()
-        .all_users(false)
-        .everyone(false)
-        .all_roles(false);
-
-    let msg = serenity::CreateMessage::default()
-        .content(&content)
-        .embed(embed)
-        .allowed_mentions(mentions);
-
-    if blacklisted {
-        if let Some(announce) = announce {
-            announce.send_message(&ctx.http, msg.clone()).await?;
-        }
-    }
-
-    if let Some(post) = post {
-        post.send_message(&ctx.http, msg).await?;
-    }
-
-    Ok(())
-}
@@ -0,0 +1,60 @@
+use chrono::Utc;
+use poise::serenity_prelude::{EmojiId, StickerId};
+use sqlx::query;
+
+use crate::Error;
+
+use moth_data::database::Database;
+
+/// Renames a tracked emote in place so its usage stays attached, recording the old name.
+pub(super) async fn rename_emote(
+    database: &Database,
+    emoji_id: EmojiId,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), Error> {
+    let mut transaction = database.db.begin().await?;
+
+    // emotes that were never used are not tracked yet, so there is nothing to rename.
+    let Some(record) = query!(
+        "UPDATE emotes SET emote_name = $1 WHERE discord_id = $2 RETURNING id",
+        new_name,
+        emoji_id.get() as i64
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    query!(
+        "INSERT INTO emote_renames (emote_id, old_name, new_name, timestamp) VALUES ($1, $2, $3, \
+         $4)",
+        record.id,
+        old_name,
+        new_name,
+        Utc::now().naive_utc()
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub(super) async fn rename_sticker(
+    database: &Database,
+    sticker_id: StickerId,
+    new_name: &str,
+) -> Result<(), Error> {
+    query!(
+        "UPDATE stickers SET sticker_name = $1 WHERE sticker_id = $2",
+        new_name,
+        sticker_id.get() as i64
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+mod database;
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::{helper::get_guild_name_override, Data, Error};
+use database::{rename_emote, rename_sticker};
+use moth_ansi::{HI_MAGENTA, RESET};
+use poise::serenity_prelude::{
+    self as serenity, Emoji, EmojiId, ExtractMap, Guild, GuildId, Sticker, StickerId,
+};
+
+/// Stores the emojis and stickers of a guild so the next update can be diffed against them.
+pub fn snapshot_guild(data: &Data, guild: &Guild) {
+    let emojis = guild
+        .emojis
+        .iter()
+        .map(|e| (e.id, e.name.to_string()))
+        .collect();
+    let stickers = guild
+        .stickers
+        .iter()
+        .map(|s| (s.id, s.name.to_string()))
+        .collect();
+
+    data.expression_snapshots.emojis.insert(guild.id, emojis);
+    data.expression_snapshots
+        .stickers
+        .insert(guild.id, stickers);
+}
+
+pub async fn guild_emojis_update(
+    ctx: &serenity::Context,
+    data: Arc<Data>,
+    guild_id: GuildId,
+    current_state: &ExtractMap<EmojiId, Emoji>,
+) -> Result<(), Error> {
+    let current = current_state
+        .iter()
+        .map(|e| (e.id, e.name.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let Some(old) = data
+        .expression_snapshots
+        .emojis
+        .insert(guild_id, current.clone())
+    else {
+        return Ok(());
+    };
+
+    let changes = NameChanges::new(&old, &current);
+    for (id, old_name, new_name) in &changes.renamed {
+        rename_emote(&data.database, *id, old_name, new_name).await?;
+    }
+
+    let diff = changes.diff();
+    if !diff.is_empty() {
+        let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
+        println!("{HI_MAGENTA}[{guild_name}] Emojis were updated!{RESET}\n{diff}");
+    }
+
+    Ok(())
+}
+
+pub async fn guild_stickers_update(
+    ctx: &serenity::Context,
+    data: Arc<Data>,
+    guild_id: GuildId,
+    current_state: &ExtractMap<StickerId, Sticker>,
+) -> Result<(), Error> {
+    let current = current_state
+        .iter()
+        .map(|s| (s.id, s.name.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let Some(old) = data
+        .expression_snapshots
+        .stickers
+        .insert(guild_id, current.clone())
+    else {
+        return Ok(());
+    };
+
+    let changes = NameChanges::new(&old, &current);
+    for (id, _, new_name) in &changes.renamed {
+        rename_sticker(&data.database, *id, new_name).await?;
+    }
+
+    let diff = changes.diff();
+    if !diff.is_empty() {
+        let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
+        println!("{HI_MAGENTA}[{guild_name}] Stickers were updated!{RESET}\n{diff}");
+    }
+
+    Ok(())
+}
+
+struct NameChanges<'a, K> {
+    added: Vec<(K, &'a str)>,
+    renamed: Vec<(K, &'a str, &'a str)>,
+    removed: Vec<(K, &'a str)>,
+}
+
+impl<'a, K: Copy + Eq + Hash + Ord + Display> NameChanges<'a, K> {
+    fn new(old: &'a HashMap<K, String>, new: &'a HashMap<K, String>) -> Self {
+        let mut added = vec![];
+        let mut renamed = vec![];
+        let mut removed = vec![];
+
+        for (id, name) in new {
+            match old.get(id) {
+                Some(old_name) if old_name != name => renamed.push((*id, &**old_name, &**name)),
+                Some(_) => {}
+                None => added.push((*id, &**name)),
+            }
+        }
+        for (id, name) in old {
+            if !new.contains_key(id) {
+                removed.push((*id, &**name));
+            }
+        }
+
+        added.sort_unstable_by_key(|(id, _)| *id);
+        renamed.sort_unstable_by_key(|(id, _, _)| *id);
+        removed.sort_unstable_by_key(|(id, _)| *id);
+
+        NameChanges {
+            added,
+            renamed,
+            removed,
+        }
+    }
+
+    fn diff(&self) -> String {
+        let mut diff = String::new();
+
+        for (id, name) in &self.added {
+            writeln!(diff, "Added: {name} (ID:{id})").unwrap();
+        }
+        for (id, old, new) in &self.renamed {
+            writeln!(diff, "Renamed: {old} -> {new} (ID:{id})").unwrap();
+        }
+        for (id, name) in &self.removed {
+            writeln!(diff, "Removed: {name} (ID:{id})").unwrap();
+        }
+
+        diff.trim_end_matches('\n').to_string()
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt::Write;
 use std::sync::Arc;
 
 use crate::{
@@ -6,7 +7,7 @@ use crate::{
 };
 use poise::serenity_prelude::{
     self as serenity, AuditLogEntry, AutoModAction, ChannelId, CreateEmbedAuthor, Guild, GuildId,
-    Member, User,
+    GuildMembersChunkEvent, Member, PartialGuild, User,
 };
 
 use moth_ansi::{RESET, YELLOW};
@@ -17,6 +18,7 @@ pub async fn guild_create(
     ctx: &serenity::Context,
     guild: &Guild,
     is_new: &Option<bool>,
+    data: Arc<Data>,
 ) -> Result<(), Error> {
     if let Some(true) = is_new {
         println!(
@@ -26,9 +28,164 @@ pub async fn guild_create(
             ctx.cache.guilds().len()
         );
     }
+
+    super::expressions::snapshot_guild(&data, guild);
+    super::voice::resume_voice_sessions(&data, guild).await?;
+    super::invites::cache_guild_invites(ctx, &data, guild.id).await;
+
+    if name_sync_enabled(&data, guild.id) {
+        let now = std::time::Instant::now();
+        let report = data.bulk_sync_names(guild.id, guild.members.iter()).await?;
+
+        println!(
+            "{YELLOW}[{}] Synced names of {} members, {} changes ({} usernames, {} global \
+             names, {} nicknames) in {}ms{RESET}",
+            guild.name,
+            report.checked,
+            report.changed(),
+            report.usernames,
+            report.global_names,
+            report.nicknames,
+            now.elapsed().as_millis()
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn guild_members_chunk(
+    ctx: &serenity::Context,
+    chunk: &GuildMembersChunkEvent,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    if !name_sync_enabled(&data, chunk.guild_id) {
+        println!(
+            "Chunk recieved containing {} members: {}/{}",
+            chunk.members.len(),
+            chunk.chunk_index + 1,
+            chunk.chunk_count
+        );
+        return Ok(());
+    }
+
+    let now = std::time::Instant::now();
+    let report = data
+        .bulk_sync_names(chunk.guild_id, chunk.members.iter())
+        .await?;
+
+    println!(
+        "{YELLOW}[{}] Name sync chunk {}/{}: {} members, {} changes ({} usernames, {} global \
+         names, {} nicknames) in {}ms{RESET}",
+        get_guild_name_override(ctx, &data, Some(chunk.guild_id)),
+        chunk.chunk_index + 1,
+        chunk.chunk_count,
+        report.checked,
+        report.changed(),
+        report.usernames,
+        report.global_names,
+        report.nicknames,
+        now.elapsed().as_millis()
+    );
+
     Ok(())
 }
 
+pub async fn guild_update(
+    ctx: &serenity::Context,
+    data: Arc<Data>,
+    old: &Option<Guild>,
+    new: &PartialGuild,
+) -> Result<(), Error> {
+    let Some(old) = old else {
+        return Ok(());
+    };
+
+    let mut diff = String::new();
+
+    if old.name != new.name {
+        writeln!(diff, "Name: {} -> {}", old.name, new.name).unwrap();
+    }
+    if old.icon != new.icon {
+        writeln!(
+            diff,
+            "Icon: {} -> {}",
+            old.icon
+                .as_ref()
+                .map_or_else(|| "None".to_string(), ToString::to_string),
+            new.icon
+                .as_ref()
+                .map_or_else(|| "None".to_string(), ToString::to_string)
+        )
+        .unwrap();
+    }
+    if old.verification_level != new.verification_level {
+        writeln!(
+            diff,
+            "Verification level: {:?} -> {:?}",
+            old.verification_level, new.verification_level
+        )
+        .unwrap();
+    }
+    if old.vanity_url_code != new.vanity_url_code {
+        writeln!(
+            diff,
+            "Vanity URL: {} -> {}",
+            old.vanity_url_code.as_deref().unwrap_or("None"),
+            new.vanity_url_code.as_deref().unwrap_or("None")
+        )
+        .unwrap();
+    }
+
+    let old_afk = old.afk_metadata.as_ref().map(|a| a.afk_channel_id);
+    let new_afk = new.afk_metadata.as_ref().map(|a| a.afk_channel_id);
+    if old_afk != new_afk {
+        writeln!(
+            diff,
+            "AFK channel: {} -> {}",
+            optional_channel_name(ctx, new.id, old_afk).await,
+            optional_channel_name(ctx, new.id, new_afk).await
+        )
+        .unwrap();
+    }
+    if old.system_channel_id != new.system_channel_id {
+        writeln!(
+            diff,
+            "System channel: {} -> {}",
+            optional_channel_name(ctx, new.id, old.system_channel_id).await,
+            optional_channel_name(ctx, new.id, new.system_channel_id).await
+        )
+        .unwrap();
+    }
+
+    let diff = diff.trim_end_matches('\n');
+    if !diff.is_empty() {
+        let guild_name = get_guild_name_override(ctx, &data, Some(new.id));
+        println!("{YELLOW}[{guild_name}] Guild was updated!{RESET}\n{diff}");
+    }
+
+    Ok(())
+}
+
+async fn optional_channel_name(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    channel_id: Option<ChannelId>,
+) -> String {
+    match channel_id {
+        Some(id) => format!("#{}", get_channel_name(ctx, Some(guild_id), id).await),
+        None => String::from("None"),
+    }
+}
+
+fn name_sync_enabled(data: &Data, guild_id: GuildId) -> bool {
+    data.config
+        .read()
+        .events
+        .name_sync_guilds
+        .as_ref()
+        .is_some_and(|g| g.contains(&guild_id))
+}
+
 pub async fn guild_member_addition(
     ctx: &serenity::Context,
     data: Arc<Data>,
@@ -39,12 +196,24 @@ pub async fn guild_member_addition(
 
     let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
 
+    let via = match super::invites::track_join(ctx, &data, new_member).await {
+        Ok(Some(via)) => format!(" (via {via})"),
+        Ok(None) => String::new(),
+        Err(e) => {
+            println!("Failed to track the invite used by {joined_user_id}: {e}");
+            String::new()
+        }
+    };
+
     println!(
-        "{YELLOW}[{}] {} (ID:{}) has joined!{RESET}",
+        "{YELLOW}[{}] {} (ID:{}) has joined!{via}{RESET}",
         guild_name,
         new_member.user.tag(),
         joined_user_id
     );
+
+    super::users::nickname::enforce_member(ctx, &data, None, new_member).await?;
+    super::raids::raid_check(ctx, &data, new_member).await?;
     Ok(())
 }
 
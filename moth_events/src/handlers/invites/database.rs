@@ -0,0 +1,34 @@
+use chrono::Utc;
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use crate::Error;
+
+use moth_data::database::{Database, JoinMethod};
+
+pub(super) async fn insert_join(
+    database: &Database,
+    guild_id: GuildId,
+    user_id: UserId,
+    method: JoinMethod,
+    code: Option<&str>,
+    inviter_id: Option<UserId>,
+) -> Result<(), Error> {
+    database.insert_user(user_id).await?;
+    database.insert_guild(guild_id).await?;
+
+    query!(
+        "INSERT INTO member_joins (guild_id, user_id, method, code, inviter_id, timestamp) VALUES \
+         ($1, $2, $3, $4, $5, $6)",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        method as JoinMethod,
+        code,
+        inviter_id.map(|i| i.get() as i64),
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+mod database;
+
+use std::collections::HashMap;
+
+use crate::{helper::get_user, Data, Error};
+use database::insert_join;
+use moth_data::{database::JoinMethod, structs::InviteUses};
+use poise::serenity_prelude::{
+    self as serenity, GuildId, InviteCreateEvent, InviteDeleteEvent, Member,
+};
+
+fn tracking_enabled(data: &Data, guild_id: GuildId) -> bool {
+    data.config
+        .read()
+        .events
+        .invite_tracking_guilds
+        .as_ref()
+        .is_some_and(|g| g.contains(&guild_id))
+}
+
+async fn fetch_invites(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+) -> Result<HashMap<String, InviteUses>, Error> {
+    let invites = guild_id.invites(&ctx.http).await?;
+
+    Ok(invites
+        .into_iter()
+        .map(|i| {
+            let uses = InviteUses {
+                inviter: i.inviter.map(|u| u.id),
+                uses: i.uses,
+                max_uses: u64::from(i.max_uses),
+            };
+            (i.code.to_string(), uses)
+        })
+        .collect())
+}
+
+/// Caches the use counts of every invite in a guild, called when the guild is received.
+pub async fn cache_guild_invites(ctx: &serenity::Context, data: &Data, guild_id: GuildId) {
+    if !tracking_enabled(data, guild_id) {
+        return;
+    }
+
+    match fetch_invites(ctx, guild_id).await {
+        Ok(invites) => {
+            data.invite_uses.insert(guild_id, invites);
+        }
+        Err(e) => println!("Failed to fetch the invites of {guild_id}: {e}"),
+    }
+}
+
+pub async fn invite_create(data: &Data, event: &InviteCreateEvent) -> Result<(), Error> {
+    let Some(guild_id) = event.guild_id else {
+        return Ok(());
+    };
+
+    if let Some(mut invites) = data.invite_uses.get_mut(&guild_id) {
+        invites.insert(
+            event.code.to_string(),
+            InviteUses {
+                inviter: event.inviter.as_ref().map(|u| u.id),
+                uses: 0,
+                max_uses: u64::from(event.max_uses),
+            },
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn invite_delete(data: &Data, event: &InviteDeleteEvent) -> Result<(), Error> {
+    let Some(guild_id) = event.guild_id else {
+        return Ok(());
+    };
+
+    if let Some(mut invites) = data.invite_uses.get_mut(&guild_id) {
+        // invites that hit their max uses are deleted around the same time as the join,
+        // so they are kept until the join has been diffed.
+        if invites.get(&*event.code).is_some_and(|i| !is_last_use(i)) {
+            invites.remove(&*event.code);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_last_use(invite: &InviteUses) -> bool {
+    invite.max_uses != 0 && invite.uses + 1 == invite.max_uses
+}
+
+/// Works out which invite a new member used, records it, and returns a description of it.
+///
+/// Returns `None` if invite tracking isn't enabled for the guild.
+pub async fn track_join(
+    ctx: &serenity::Context,
+    data: &Data,
+    member: &Member,
+) -> Result<Option<String>, Error> {
+    let guild_id = member.guild_id;
+    if !tracking_enabled(data, guild_id) {
+        return Ok(None);
+    }
+
+    let current = fetch_invites(ctx, guild_id).await?;
+    let old = data
+        .invite_uses
+        .insert(guild_id, current.clone())
+        .unwrap_or_default();
+
+    let candidates = current
+        .iter()
+        .filter(|(code, new)| old.get(*code).map_or(new.uses > 0, |o| new.uses > o.uses))
+        .chain(
+            old.iter()
+                .filter(|(code, o)| !current.contains_key(*code) && is_last_use(o)),
+        )
+        .collect::<Vec<_>>();
+
+    let vanity = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.vanity_url_code.as_ref().map(ToString::to_string));
+
+    let (method, code, inviter) = match (candidates.as_slice(), vanity) {
+        ([(code, invite)], _) => (JoinMethod::Invite, Some(code.as_str()), invite.inviter),
+        // no invite gained a use, so it can only have been the vanity url.
+        ([], Some(_)) => (JoinMethod::Vanity, None, None),
+        _ => (JoinMethod::Unknown, None, None),
+    };
+
+    insert_join(
+        &data.database,
+        guild_id,
+        member.user.id,
+        method,
+        code,
+        inviter,
+    )
+    .await?;
+
+    let inviter = match inviter {
+        Some(inviter) => get_user(ctx, guild_id, inviter).await.map(|u| u.tag()),
+        None => None,
+    };
+
+    let description = match (method, code, inviter) {
+        (JoinMethod::Invite, Some(code), Some(inviter)) => format!("invite {code} by {inviter}"),
+        (JoinMethod::Invite, Some(code), None) => format!("invite {code}"),
+        (JoinMethod::Vanity, _, _) => String::from("the vanity url"),
+        _ => String::from("an unknown invite"),
+    };
+
+    Ok(Some(description))
+}
@@ -1,89 +1,274 @@
-use crate::Data;
-use moth_data::structs::{Decay, InnerCache};
-use poise::serenity_prelude as serenity;
-use serenity::{ChannelId, GetMessages, GuildId, MessageId, UserId};
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::database::message_author;
+use crate::helper::{get_channel_name, get_guild_name_override};
+use crate::moderation::{guild_config, log_to};
+use crate::{Data, Error};
+use moth_ansi::{HI_RED, RESET};
+use moth_data::structs::{DeleteAttribution, DeleteBurst};
+use poise::serenity_prelude::{
+    self as serenity,
+    audit_log::{Action, MessageAction},
+    AuditLogEntry, ChannelId, CreateEmbed, CreateMessage, GetMessages, GuildId, MessageId, UserId,
+};
 
 async fn fetch(
     ctx: &serenity::Context,
-    channel_id: &ChannelId,
-    guild_id: &GuildId,
-    deleted_message_id: &MessageId,
     data: &Arc<Data>,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    deleted_message_id: MessageId,
     fetch_newer: bool,
 ) {
     let builder = if fetch_newer {
-        GetMessages::new().after(*deleted_message_id).limit(100)
+        GetMessages::new().after(deleted_message_id).limit(100)
     } else {
-        GetMessages::new().before(*deleted_message_id).limit(100)
+        GetMessages::new().before(deleted_message_id).limit(100)
     };
     println!("Fetching from http.");
     let Ok(msgs) = channel_id.messages(&ctx, builder).await else {
         return;
     };
-    if let Some(mut value) = data.anti_delete_cache.map.get_mut(guild_id) {
-        for msg in msgs {
-            value.msg_user_cache.insert(msg.id, msg.author.id);
-        }
-    } else {
-        let mut map = HashMap::new();
+
+    if let Some(mut burst) = data.anti_delete_cache.bursts.get_mut(&guild_id) {
         for msg in msgs {
-            map.insert(msg.id, msg.author.id);
+            burst.msg_user_cache.insert(msg.id, msg.author.id);
         }
-        data.anti_delete_cache.map.insert(
-            *guild_id,
-            InnerCache {
-                last_deleted_msg: *deleted_message_id,
-                msg_user_cache: map,
-            },
-        );
     }
 }
-pub async fn anti_delete(
+
+/// Finds the author of a deleted message, checking the database before falling back to http.
+async fn resolve_author(
     ctx: &serenity::Context,
     data: &Arc<Data>,
-    channel_id: &ChannelId,
-    guild_id: &GuildId,
-    deleted_message_id: &MessageId,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    deleted_message_id: MessageId,
+    last_deleted: Option<MessageId>,
 ) -> Option<UserId> {
-    // increase value.
-    {
-        let Some(mut value) = data.anti_delete_cache.val.get_mut(guild_id) else {
-            data.anti_delete_cache.val.insert(
-                *guild_id,
-                Decay {
-                    val: 1,
-                    last_update: Instant::now(),
-                },
-            );
-            return None;
+    if let Ok(Some(author)) = message_author(&data.database, deleted_message_id).await {
+        return Some(author);
+    }
+
+    let cached = || {
+        data.anti_delete_cache
+            .bursts
+            .get(&guild_id)
+            .and_then(|b| b.msg_user_cache.get(&deleted_message_id).copied())
+    };
+
+    if let Some(author) = cached() {
+        return Some(author);
+    }
+
+    // deletions usually walk through a channel in one direction, so fetch the messages that
+    // are likely to be deleted next.
+    let fetch_newer = last_deleted.is_some_and(|l| l < deleted_message_id);
+    fetch(
+        ctx,
+        data,
+        channel_id,
+        guild_id,
+        deleted_message_id,
+        fetch_newer,
+    )
+    .await;
+
+    cached()
+}
+
+/// Heats up the guild on every deletion, resolving authors and alerting once the configured heat
+/// is reached.
+pub async fn anti_delete(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    deleted_message_id: MessageId,
+    cached_author: Option<UserId>,
+) -> Result<(), Error> {
+    let Some(config) = guild_config(data, guild_id, |e| e.anti_delete.as_ref()) else {
+        return Ok(());
+    };
+
+    let (heat, last_deleted) = {
+        let mut burst = data
+            .anti_delete_cache
+            .bursts
+            .entry(guild_id)
+            .or_insert_with(DeleteBurst::new);
+
+        burst.heat = (burst.heat + 1).min(config.max_heat);
+        burst.last_update = Instant::now();
+        *burst.channels.entry(channel_id).or_default() += 1;
+        let last_deleted = burst.last_deleted_msg.replace(deleted_message_id);
+
+        (burst.heat, last_deleted)
+    };
+
+    // low heat = no check.
+    let author = match cached_author {
+        Some(author) => Some(author),
+        None if heat >= config.check_heat => {
+            resolve_author(
+                ctx,
+                data,
+                channel_id,
+                guild_id,
+                deleted_message_id,
+                last_deleted,
+            )
+            .await
+        }
+        None => message_author(&data.database, deleted_message_id)
+            .await
+            .ok()
+            .flatten(),
+    };
+
+    let should_alert = {
+        let Some(mut burst) = data.anti_delete_cache.bursts.get_mut(&guild_id) else {
+            return Ok(());
         };
-        if value.val > 0 && value.val < 5 {
-            value.val += 1;
-            // low heat = no check.
-            if value.val < 3 {
-                return None;
-            }
+
+        if let Some(author) = author {
+            *burst.authors.entry(author).or_default() += 1;
         }
+
+        let should_alert = heat >= config.check_heat && !burst.alerted;
+        burst.alerted |= should_alert;
+        should_alert
+    };
+
+    if !should_alert {
+        return Ok(());
     }
-    let last_deleted = {
-        let Some(mut value) = data.anti_delete_cache.map.get_mut(guild_id) else {
-            fetch(ctx, channel_id, guild_id, deleted_message_id, data, false).await;
-            return None;
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+    let channel_name = get_channel_name(ctx, Some(guild_id), channel_id).await;
+    println!("{HI_RED}[{guild_name}] [#{channel_name}] Possible mass deletion{RESET}");
+
+    if let Some(alert_channel) = config.alert_channel {
+        let triggered_on = author.map_or_else(
+            || String::from("an unknown author"),
+            |author| format!("<@{author}>"),
+        );
+        let embed = CreateEmbed::new()
+            .title("Possible mass deletion?")
+            .description(format!(
+                "Triggered on {triggered_on} in <#{channel_id}>\nA summary will follow once the \
+                 deletions stop."
+            ));
+        log_to(ctx, alert_channel, CreateMessage::new().embed(embed)).await;
+    }
+
+    Ok(())
+}
+
+/// Attributes message deletions to the moderator or bot doing them through the audit log.
+///
+/// Deleting your own messages doesn't create an audit log entry, so only deletions of other
+/// users messages are attributed.
+pub fn anti_delete_audit(data: &Data, entry: &AuditLogEntry, guild_id: GuildId) {
+    if !matches!(entry.action, Action::Message(MessageAction::Delete)) {
+        return;
+    }
+
+    let Some(deleter) = entry.user_id else {
+        return;
+    };
+
+    if guild_config(data, guild_id, |e| e.anti_delete.as_ref()).is_none() {
+        return;
+    }
+
+    // discord merges deletions by the same moderator into one entry, counting them.
+    let options = entry.options.as_ref();
+    let count = options
+        .and_then(|o| o.count)
+        .map_or(1, |c| u32::try_from(c).unwrap_or(u32::MAX));
+
+    // the entry can arrive before the deletion itself, so it is only merged into the burst once
+    // that finishes.
+    data.anti_delete_cache
+        .attributions
+        .entry(guild_id)
+        .or_default()
+        .push(DeleteAttribution {
+            deleter,
+            channel_id: options.and_then(|o| o.channel_id),
+            count,
+            received: Instant::now(),
+        });
+}
+
+/// Decays the heat of every guild, sending a summary of the bursts that were alerted on and have
+/// now stopped.
+pub async fn finish_bursts(ctx: &serenity::Context, data: &Arc<Data>) {
+    let configs = data
+        .config
+        .read()
+        .events
+        .anti_delete
+        .clone()
+        .unwrap_or_default();
+
+    let finished = data.anti_delete_cache.decay_proc(|guild_id| {
+        Duration::from_secs(configs.get(&guild_id).map_or(5, |c| c.decay_secs))
+    });
+
+    for (guild_id, burst) in finished {
+        if !burst.alerted {
+            continue;
+        }
+
+        let Some(alert_channel) = configs.get(&guild_id).and_then(|c| c.alert_channel) else {
+            continue;
         };
-        let last_deleted = value.last_deleted_msg;
-        value.last_deleted_msg = *deleted_message_id;
-        for (m, u) in &value.value().msg_user_cache {
-            if m == deleted_message_id {
-                return Some(*u);
-            }
+
+        let embed = CreateEmbed::new()
+            .title("Mass deletion summary")
+            .description(burst_summary(&burst));
+        log_to(ctx, alert_channel, CreateMessage::new().embed(embed)).await;
+    }
+}
+
+fn sorted_counts<K: Copy>(counts: &HashMap<K, u32>) -> Vec<(K, u32)> {
+    let mut counts = counts.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    counts.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+    counts
+}
+
+fn burst_summary(burst: &DeleteBurst) -> String {
+    let total = burst.channels.values().sum::<u32>();
+    let mut summary = format!("**{total}** messages were deleted.\n\n**Channels:**\n");
+
+    for (channel_id, count) in sorted_counts(&burst.channels).into_iter().take(10) {
+        writeln!(summary, "<#{channel_id}>: {count}").unwrap();
+    }
+
+    if !burst.authors.is_empty() {
+        summary.push_str("\n**Authors:**\n");
+        for (user_id, count) in sorted_counts(&burst.authors).into_iter().take(10) {
+            writeln!(summary, "<@{user_id}>: {count}").unwrap();
         }
-        last_deleted
-    };
-    if last_deleted < *deleted_message_id {
-        fetch(ctx, channel_id, guild_id, deleted_message_id, data, true).await;
+    }
+
+    summary.push_str("\n**Deleted by:**\n");
+    if burst.deleters.is_empty() {
+        summary.push_str("Nobody in the audit log, the authors likely deleted these themselves.");
     } else {
-        fetch(ctx, channel_id, guild_id, deleted_message_id, data, false).await;
+        for ((user_id, channel_id), count) in sorted_counts(&burst.deleters).into_iter().take(10) {
+            match channel_id {
+                Some(channel_id) => writeln!(summary, "<@{user_id}> in <#{channel_id}>: {count}"),
+                None => writeln!(summary, "<@{user_id}>: {count}"),
+            }
+            .unwrap();
+        }
     }
-    None
+
+    summary.trim_end_matches('\n').to_string()
 }
@@ -6,8 +6,8 @@ use sqlx::query;
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::Error;
-use moth_data::database::{Database, EmoteUsageType};
-use poise::serenity_prelude::Message;
+use moth_data::database::{Database, EmoteUsageType, MessageAction};
+use poise::serenity_prelude::{GuildId, Message, MessageId, UserId};
 
 pub static EMOJI_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"<(a)?:([a-zA-Z0-9_]{2,32}):(\d{1,20})>").unwrap());
@@ -104,8 +104,7 @@ pub(super) async fn insert_message(database: &Database, message: &Message) -> Re
             // &captures[3] is id.
             let id = query!(
                 "INSERT INTO emotes (emote_name, discord_id) VALUES ($1, $2) ON CONFLICT \
-                 (emote_name, discord_id) DO UPDATE SET emote_name = EXCLUDED.emote_name \
-                 RETURNING id",
+                 (discord_id) DO UPDATE SET emote_name = EXCLUDED.emote_name RETURNING id",
                 &captures[2],
                 *id as i64
             )
@@ -210,3 +209,118 @@ pub(super) async fn insert_deletion(database: &Database, message: &Message) -> R
 
     Ok(())
 }
+
+/// An invite found in a message, with the guild it points to if it was valid.
+pub(super) struct InvitePost<'a> {
+    pub code: &'a str,
+    pub target: Option<(GuildId, &'a str)>,
+    pub allowed: bool,
+    pub action: MessageAction,
+    /// If every part of the action went through.
+    pub succeeded: bool,
+}
+
+pub(super) async fn insert_invite_posts(
+    database: &Database,
+    message: &Message,
+    posts: &[InvitePost<'_>],
+) -> Result<(), Error> {
+    let Some(guild_id) = message.guild_id else {
+        return Ok(());
+    };
+    if posts.is_empty() {
+        return Ok(());
+    }
+
+    database.insert_user(message.author.id).await?;
+    database.insert_guild(guild_id).await?;
+
+    let codes = posts.iter().map(|p| p.code).collect::<Vec<_>>();
+    let target_ids = posts
+        .iter()
+        .map(|p| p.target.map(|(id, _)| id.get() as i64))
+        .collect::<Vec<_>>();
+    let target_names = posts
+        .iter()
+        .map(|p| p.target.map(|(_, name)| name))
+        .collect::<Vec<_>>();
+    let allowed = posts.iter().map(|p| p.allowed).collect::<Vec<_>>();
+    let actions = posts.iter().map(|p| p.action).collect::<Vec<_>>();
+    let succeeded = posts.iter().map(|p| p.succeeded).collect::<Vec<_>>();
+
+    query!(
+        "INSERT INTO invite_posts (guild_id, channel_id, message_id, user_id, code, \
+         target_guild_id, target_guild_name, allowed, action, succeeded, timestamp) SELECT $1, \
+         $2, $3, $4, code, target_id, target_name, allowed, action, succeeded, $11 FROM \
+         UNNEST($5::TEXT[], $6::BIGINT[], $7::TEXT[], $8::BOOLEAN[], $9::MessageAction[], \
+         $10::BOOLEAN[]) AS p(code, target_id, target_name, allowed, action, succeeded)",
+        guild_id.get() as i64,
+        message.channel_id.get() as i64,
+        message.id.get() as i64,
+        message.author.id.get() as i64,
+        &codes as &[&str],
+        &target_ids as &[Option<i64>],
+        &target_names as &[Option<&str>],
+        &allowed,
+        &actions as &[MessageAction],
+        &succeeded,
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
+
+/// Looks up the author of a logged message.
+pub(super) async fn message_author(
+    database: &Database,
+    message_id: MessageId,
+) -> Result<Option<UserId>, Error> {
+    let record = query!(
+        "SELECT user_id FROM messages WHERE message_id = $1",
+        message_id.get() as i64
+    )
+    .fetch_optional(&database.db)
+    .await?;
+
+    Ok(record
+        .and_then(|r| r.user_id)
+        .map(|id| UserId::new(id as u64)))
+}
+
+pub(super) async fn insert_filter_action(
+    database: &Database,
+    message: &Message,
+    severity: Option<&str>,
+    badlist_words: &[&str],
+    action: MessageAction,
+    succeeded: bool,
+) -> Result<(), Error> {
+    let Some(guild_id) = message.guild_id else {
+        return Ok(());
+    };
+
+    database.insert_guild(guild_id).await?;
+    database.insert_user(message.author.id).await?;
+
+    query!(
+        "INSERT INTO filter_actions (guild_id, channel_id, message_id, user_id, content, \
+         severity, badlist_words, action, succeeded, timestamp) VALUES ($1, $2, $3, $4, $5, $6, \
+         $7, $8, $9, $10)",
+        guild_id.get() as i64,
+        message.channel_id.get() as i64,
+        message.id.get() as i64,
+        message.author.id.get() as i64,
+        &*message.content,
+        severity,
+        badlist_words as &[&str],
+        action as MessageAction,
+        succeeded,
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
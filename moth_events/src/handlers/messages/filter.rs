@@ -0,0 +1,138 @@
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::database::insert_filter_action;
+use crate::helper::get_guild_name_override;
+use crate::moderation::{apply_message_action, guild_config, has_any_role, log_to};
+use crate::{Data, Error};
+use moth_ansi::{HI_RED, RESET};
+use moth_config::{FilterModeration, ModerationAction};
+use moth_filter::{Scan, Severity};
+use poise::serenity_prelude::{
+    self as serenity, CreateAllowedMentions, CreateEmbed, CreateEmbedAuthor, CreateMessage, Message,
+};
+
+const REASON: &str = "Flagged by the filter";
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Mild => "Mild",
+        Severity::Moderate => "Moderate",
+        Severity::Severe => "Severe",
+    }
+}
+
+/// Picks the strongest action configured for what the scan found.
+fn pick_action(config: &FilterModeration, scan: &Scan<'_>) -> Option<ModerationAction> {
+    let severity_action = match scan.severity() {
+        Some(Severity::Mild) => config.mild,
+        Some(Severity::Moderate) => config.moderate,
+        Some(Severity::Severe) => config.severe,
+        None => None,
+    };
+
+    let badlist_action = if scan.badlist_words.is_empty() {
+        None
+    } else {
+        config.badlist
+    };
+
+    severity_action.max(badlist_action)
+}
+
+/// Acts on messages the filter flagged, depending on the guilds configuration.
+pub async fn moderate_filter(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+    scan: &Scan<'_>,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() || (scan.badlist_words.is_empty() && scan.severity().is_none()) {
+        return Ok(());
+    }
+
+    let Some(config) = guild_config(data, guild_id, |e| e.filter_moderation.as_ref()) else {
+        return Ok(());
+    };
+
+    if config.exempt_channels.contains(&msg.channel_id) || has_any_role(msg, &config.bypass_roles) {
+        return Ok(());
+    }
+
+    let Some(action) = pick_action(&config, scan) else {
+        return Ok(());
+    };
+
+    let taken = apply_message_action(
+        ctx,
+        msg,
+        action,
+        Duration::from_secs(config.timeout_secs),
+        REASON,
+    )
+    .await;
+    let severity = scan.severity().map(severity_name);
+
+    insert_filter_action(
+        &data.database,
+        msg,
+        severity,
+        &scan.badlist_words,
+        taken.action,
+        taken.succeeded,
+    )
+    .await?;
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+    println!(
+        "{HI_RED}[{guild_name}] A message by {} was flagged by the filter: {}{RESET}",
+        msg.author.tag(),
+        taken.description
+    );
+
+    let Some(log_channel) = config.log_channel else {
+        return Ok(());
+    };
+
+    let mut description = format!("**Channel:** <#{}>\n", msg.channel_id);
+    if let Some(severity) = severity {
+        writeln!(description, "**Severity:** {severity}").unwrap();
+    }
+    if !scan.badlist_words.is_empty() {
+        let words = scan
+            .badlist_words
+            .iter()
+            .map(|w| format!("`{w}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(description, "**Badlist:** {words}").unwrap();
+    }
+
+    let preview = msg.content.chars().take(1000).collect::<String>();
+    write!(
+        description,
+        "**Action:** {}\n[Jump to message]({})\n\n>>> {preview}",
+        taken.description,
+        msg.link()
+    )
+    .unwrap();
+
+    let embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::from(&msg.author))
+        .title("Filtered message")
+        .description(description);
+    let builder = CreateMessage::new().embed(embed).allowed_mentions(
+        CreateAllowedMentions::new()
+            .all_users(false)
+            .all_roles(false)
+            .everyone(false),
+    );
+    log_to(ctx, log_channel, builder).await;
+
+    Ok(())
+}
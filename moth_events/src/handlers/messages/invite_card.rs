@@ -0,0 +1,224 @@
+use std::fmt::Write;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use moth_data::{lru::ShardedLru, structs::Data};
+use poise::serenity_prelude::{Invite, InviteGuild};
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg,
+};
+
+use crate::Error;
+
+/// Rendered cards by invite code and when they were rendered, so the same invite being posted
+/// again skips rendering.
+static CARDS: LazyLock<ShardedLru<String, (Instant, Arc<Vec<u8>>)>> =
+    LazyLock::new(|| ShardedLru::new(256));
+
+/// How long a card is reused before rendering it again, member counts and names go stale.
+const CARD_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Fonts are only looked up once, loading them is slow.
+static FONTS: LazyLock<Arc<usvg::fontdb::Database>> = LazyLock::new(|| {
+    let mut fonts = usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+const WIDTH: u32 = 440;
+const HEIGHT: u32 = 130;
+
+/// Returns a PNG of a card like the one shown for invites in the Discord client.
+pub(super) async fn invite_card(data: &Data, invite: &Invite) -> Result<Arc<Vec<u8>>, Error> {
+    let code = invite.code.to_string();
+    if let Some((rendered, card)) = CARDS.get(&code) {
+        if rendered.elapsed() < CARD_TTL {
+            return Ok(card);
+        }
+    }
+
+    let Some(guild) = &invite.guild else {
+        return Err("This invite is not for a guild.".into());
+    };
+
+    // a missing icon is not worth failing the whole card over.
+    let icon = match &guild.icon {
+        Some(hash) => fetch_icon(data, guild, &hash.to_string()).await.ok(),
+        None => None,
+    };
+
+    let svg = card_svg(invite, guild, icon.as_deref());
+    let card = Arc::new(render(&svg)?);
+
+    CARDS.insert(code, (Instant::now(), card.clone()));
+    Ok(card)
+}
+
+async fn fetch_icon(data: &Data, guild: &InviteGuild, hash: &str) -> Result<String, Error> {
+    let bytes = data
+        .reqwest
+        .get(format!(
+            "https://cdn.discordapp.com/icons/{}/{hash}.png?size=128",
+            guild.id
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+fn card_svg(invite: &Invite, guild: &InviteGuild, icon: Option<&str>) -> String {
+    // long names would run off the card.
+    let mut name = guild.name.chars().take(28).collect::<String>();
+    if guild.name.chars().count() > 28 {
+        name.push('…');
+    }
+    let name = escape(&name);
+    let mut svg = String::new();
+
+    write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">
+<defs><clipPath id="icon"><rect x="16" y="46" width="56" height="56" rx="16"/></clipPath></defs>
+<rect width="{WIDTH}" height="{HEIGHT}" rx="8" fill="#2b2d31"/>
+<text x="16" y="30" font-family="sans-serif" font-size="12" font-weight="bold" fill="#b5bac1">YOU'VE BEEN INVITED TO JOIN A SERVER</text>
+"##
+    )
+    .unwrap();
+
+    if let Some(icon) = icon {
+        writeln!(
+            svg,
+            r#"<image x="16" y="46" width="56" height="56" clip-path="url(#icon)" href="data:image/png;base64,{icon}"/>"#
+        )
+        .unwrap();
+    } else {
+        writeln!(
+            svg,
+            r##"<rect x="16" y="46" width="56" height="56" rx="16" fill="#313338"/>
+<text x="44" y="80" text-anchor="middle" font-family="sans-serif" font-size="16" fill="#dbdee1">{}</text>"##,
+            escape(&acronym(&guild.name))
+        )
+        .unwrap();
+    }
+
+    let mut name_x = 88;
+    if let Some(colour) = badge_colour(guild) {
+        writeln!(
+            svg,
+            r#"<circle cx="96" cy="66" r="8" fill="{colour}"/>
+<path d="M92 66 l3 3 l5 -6" stroke="white" stroke-width="2" fill="none"/>"#
+        )
+        .unwrap();
+        name_x += 22;
+    }
+
+    writeln!(
+        svg,
+        r##"<text x="{name_x}" y="72" font-family="sans-serif" font-size="16" font-weight="bold" fill="#f2f3f5">{name}</text>"##
+    )
+    .unwrap();
+
+    let online = invite.approximate_presence_count.unwrap_or_default();
+    let members = invite.approximate_member_count.unwrap_or_default();
+    writeln!(
+        svg,
+        r##"<circle cx="92" cy="90" r="4" fill="#23a55a"/>
+<text x="100" y="94" font-family="sans-serif" font-size="12" fill="#b5bac1">{} Online</text>
+<circle cx="190" cy="90" r="4" fill="#80848e"/>
+<text x="198" y="94" font-family="sans-serif" font-size="12" fill="#b5bac1">{} Members</text>"##,
+        format_count(online),
+        format_count(members)
+    )
+    .unwrap();
+
+    let level = boost_level(guild.premium_subscription_count.unwrap_or_default());
+    if level > 0 {
+        writeln!(
+            svg,
+            r##"<text x="{}" y="30" text-anchor="end" font-family="sans-serif" font-size="12" font-weight="bold" fill="#ff73fa">BOOST LEVEL {level}</text>"##,
+            WIDTH - 16
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn render(svg: &str) -> Result<Vec<u8>, Error> {
+    let options = usvg::Options {
+        fontdb: FONTS.clone(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+
+    let Some(mut pixmap) = Pixmap::new(WIDTH, HEIGHT) else {
+        return Err("Failed to create a pixmap.".into());
+    };
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+
+    Ok(pixmap.encode_png()?)
+}
+
+/// Verified guilds get a green badge and partnered guilds a blue one, like in the client.
+fn badge_colour(guild: &InviteGuild) -> Option<&'static str> {
+    let has = |feature: &str| {
+        guild
+            .features
+            .iter()
+            .any(|f| f.eq_ignore_ascii_case(feature))
+    };
+
+    if has("VERIFIED") {
+        Some("#23a55a")
+    } else if has("PARTNERED") {
+        Some("#5865f2")
+    } else {
+        None
+    }
+}
+
+fn boost_level(boosts: u64) -> u8 {
+    match boosts {
+        14.. => 3,
+        7.. => 2,
+        2.. => 1,
+        _ => 0,
+    }
+}
+
+/// The first letter of every word, which the client shows for guilds without an icon.
+fn acronym(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(3)
+        .collect()
+}
+
+fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index != 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
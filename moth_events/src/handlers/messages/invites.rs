@@ -1,12 +1,19 @@
 use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
+use moth_config::{InviteModeration, ModerationAction};
+use moth_data::database::MessageAction;
 use moth_data::structs::Data;
-use poise::serenity_prelude::{self as serenity, ChannelId, CreateMessage, GuildId, Message};
+use poise::serenity_prelude::{
+    self as serenity, CreateMessage, GuildId, HttpError, Invite, JsonErrorCode, Message,
+};
 use regex::Regex;
 
 use ::serenity::all::{CreateAllowedMentions, CreateEmbedAuthor};
-use resvg::{tiny_skia::Pixmap, usvg::Tree};
 
+use super::database::{insert_invite_posts, InvitePost};
+use super::invite_card::invite_card;
+use crate::moderation::{apply_message_action, guild_config, has_any_role, log_to, TakenAction};
 use crate::Error;
 
 pub static INVITE: LazyLock<Regex> =
@@ -21,109 +28,191 @@ pub async fn moderate_invites(
         return Ok(());
     };
 
-    if guild_id != GuildId::new(98226572468690944) {
+    let Some(config) = guild_config(data, guild_id, |e| e.invite_moderation.as_ref()) else {
         return Ok(());
     };
 
-    let mut invites = Vec::new();
-    for invite in INVITE.find_iter(&msg.content) {
-        let Ok(details) = ctx
-            .http
-            .get_invite(invite.as_str(), false, false, None)
-            .await
-        else {
-            continue;
-        };
-
-        let Some(guild) = details.guild else {
-            continue;
-        };
-
-        invites.push((details.code, guild.name));
-    }
+    let mut codes = INVITE
+        .captures_iter(&msg.content)
+        .filter_map(|c| c.get(1))
+        .map(|c| c.as_str())
+        .collect::<Vec<_>>();
+    codes.sort_unstable();
+    codes.dedup();
 
-    let mut embeds = Vec::with_capacity(invites.len());
-    let mut builder = CreateMessage::new().allowed_mentions(
-        CreateAllowedMentions::new()
-            .all_users(false)
-            .all_roles(false)
-            .everyone(false),
-    );
-    let mut first_name = None;
-    for (index, (code, name)) in invites.iter().enumerate() {
-        let Ok(response) = data
-            .reqwest
-            .get(format!("https://invidget.switchblade.xyz/{code}"))
-            .send()
-            .await
-        else {
-            continue;
-        };
-
-        // https://github.com/SwitchbladeBot/invidget/pull/82
-        let text = response.text().await?.replace("image/jpg", "image/jpeg");
-
-        let Ok(png_data) = convert_svg_to_png(&text) else {
-            continue;
-        };
-
-        let attachment_name = format!("{index}.png");
-        let attachment = serenity::CreateAttachment::bytes(png_data, attachment_name.clone());
-        builder = builder.add_file(attachment);
-
-        let mut embed = serenity::CreateEmbed::new()
-            .attachment(attachment_name)
-            .description(format!("https://discord.gg/{code}"));
+    if codes.is_empty() {
+        return Ok(());
+    }
 
-        if index == 0 {
-            embed = embed.author(CreateEmbedAuthor::from(&msg.author));
+    let mut invites = Vec::new();
+    let mut invalid = Vec::new();
+    for code in codes {
+        match ctx.http.get_invite(code, true, false, None).await {
+            Ok(invite) if invite.guild.is_some() => invites.push(invite),
+            // group dm invites aren't advertising anything.
+            Ok(_) => {}
+            Err(e) if is_unknown_invite(&e) => invalid.push(code),
+            // rate limits and outages say nothing about the invite, so it isn't recorded at all.
+            Err(e) => println!("Failed to look up invite {code}: {e}"),
         }
+    }
 
-        if first_name.is_none() {
-            first_name = Some(name);
-        };
+    let exempt = config.allowed_channels.contains(&msg.channel_id)
+        || has_any_role(msg, &config.bypass_roles);
+    let allowed = invites
+        .iter()
+        .map(|i| exempt || is_allowed(&config, guild_id, i))
+        .collect::<Vec<_>>();
+
+    let taken = if allowed.iter().all(|a| *a) {
+        TakenAction {
+            action: MessageAction::Allowed,
+            succeeded: true,
+            description: String::from("Allowed"),
+        }
+    } else {
+        apply_message_action(
+            ctx,
+            msg,
+            config.action,
+            Duration::from_secs(config.timeout_secs),
+            "Posted an invite to another server",
+        )
+        .await
+    };
 
-        if embeds.len() < 10 {
-            embeds.push(embed);
+    let posts = invites
+        .iter()
+        .zip(&allowed)
+        .map(|(invite, allowed)| InvitePost {
+            code: &invite.code,
+            target: invite.guild.as_ref().map(|g| (g.id, &*g.name)),
+            allowed: *allowed,
+            action: if *allowed {
+                MessageAction::Allowed
+            } else {
+                taken.action
+            },
+            succeeded: *allowed || taken.succeeded,
+        })
+        .chain(invalid.iter().map(|code| InvitePost {
+            code,
+            target: None,
+            allowed: false,
+            action: MessageAction::Invalid,
+            succeeded: true,
+        }))
+        .collect::<Vec<_>>();
+
+    insert_invite_posts(&data.database, msg, &posts).await?;
+
+    if let Some(log_channel) = config.log_channel {
+        if !invites.is_empty() {
+            let builder = invite_log(data, msg, &invites, &allowed, &taken, config.action).await;
+            log_to(ctx, log_channel, builder).await;
         }
     }
 
-    builder = builder.embeds(&embeds);
-
-    if !embeds.is_empty() {
-        if embeds.len() == 1 {
-            builder = builder.content(format!(
-                "{} posted an invite to {} in <#{}>",
-                msg.author,
-                first_name.expect("This should always be populated."),
-                msg.channel_id
-            ));
-        } else {
-            builder = builder.content(format!(
-                "{} posted multiple invites in <#{}>",
-                msg.author, msg.channel_id
-            ));
+    if let Some(invalid_channel) = config.invalid_log_channel {
+        if !invalid.is_empty() {
+            let codes = invalid
+                .iter()
+                .map(|c| format!("`{c}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let builder = CreateMessage::new()
+                .content(format!(
+                    "{} posted expired or invalid invites in <#{}>: {codes}",
+                    msg.author, msg.channel_id
+                ))
+                .allowed_mentions(no_mentions());
+            log_to(ctx, invalid_channel, builder).await;
         }
-
-        ChannelId::new(277163440999628800)
-            .send_message(&ctx.http, builder)
-            .await?;
     }
 
     Ok(())
 }
 
-fn convert_svg_to_png(svg_data: &str) -> Result<Vec<u8>, Error> {
-    let tree = Tree::from_str(svg_data, &usvg::Options::default())?;
+/// Only Unknown Invite means the invite is expired or invalid, anything else could be ours.
+fn is_unknown_invite(error: &serenity::Error) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.error.code == JsonErrorCode::UnknownInvite
+    )
+}
+
+fn is_allowed(config: &InviteModeration, guild_id: GuildId, invite: &Invite) -> bool {
+    let target = invite.guild.as_ref().map(|g| g.id);
+
+    target == Some(guild_id)
+        || target.is_some_and(|t| config.allowed_guilds.contains(&t))
+        || config
+            .allowed_codes
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(&invite.code))
+}
+
+fn no_mentions() -> CreateAllowedMentions<'static> {
+    CreateAllowedMentions::new()
+        .all_users(false)
+        .all_roles(false)
+        .everyone(false)
+}
 
-    let size = tree.size().to_int_size();
-    let mut pixmap = Pixmap::new(size.width(), size.height()).expect("Failed to create a pixmap");
+async fn invite_log<'a>(
+    data: &Data,
+    msg: &Message,
+    invites: &[Invite],
+    allowed: &[bool],
+    taken: &TakenAction,
+    configured: ModerationAction,
+) -> CreateMessage<'a> {
+    let mut embeds = Vec::with_capacity(invites.len());
+    let mut builder = CreateMessage::new().allowed_mentions(no_mentions());
+
+    for (index, (invite, allowed)) in invites.iter().zip(allowed).enumerate().take(10) {
+        let code = &invite.code;
+        let mut embed = serenity::CreateEmbed::new().description(format!(
+            "https://discord.gg/{code}{}",
+            if *allowed { " (allowed)" } else { "" }
+        ));
+
+        if let Ok(png_data) = invite_card(data, invite).await {
+            let attachment_name = format!("{index}.png");
+            let attachment =
+                serenity::CreateAttachment::bytes(png_data.to_vec(), attachment_name.clone());
+            builder = builder.add_file(attachment);
+            embed = embed.attachment(attachment_name);
+        }
 
-    resvg::render(
-        &tree,
-        resvg::tiny_skia::Transform::default(),
-        &mut pixmap.as_mut(),
-    );
+        if index == 0 {
+            embed = embed.author(CreateEmbedAuthor::from(&msg.author));
+        }
+
+        embeds.push(embed);
+    }
+
+    let content = if let [invite] = invites {
+        format!(
+            "{} posted an invite to {} in <#{}>",
+            msg.author,
+            invite.guild.as_ref().map_or("Unknown", |g| &*g.name),
+            msg.channel_id
+        )
+    } else {
+        format!(
+            "{} posted multiple invites in <#{}>",
+            msg.author, msg.channel_id
+        )
+    };
+
+    // only mention the action if one was configured and something was disallowed.
+    let content = if configured == ModerationAction::Log || taken.action == MessageAction::Allowed {
+        content
+    } else {
+        format!("{content}\n**Action:** {}", taken.description)
+    };
 
-    Ok(pixmap.encode_png()?)
+    builder.content(content).embeds(embeds)
 }
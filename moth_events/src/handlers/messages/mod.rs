@@ -2,10 +2,16 @@ use std::fmt::Write;
 use std::sync::Arc;
 
 mod anti_delete;
+pub use anti_delete::{anti_delete_audit, finish_bursts};
 mod database;
 pub use database::EMOJI_REGEX;
+mod filter;
+use filter::moderate_filter;
+mod invite_card;
 use invites::moderate_invites;
 mod invites;
+mod spam;
+use spam::detect_spam;
 
 use crate::helper::{get_channel_name, get_guild_name, get_guild_name_override};
 use crate::{Data, Error};
@@ -14,28 +20,41 @@ use moth_ansi::{CYAN, DIM, HI_BLACK, HI_RED, RESET};
 
 use database::{insert_deletion, insert_edit, insert_message};
 use poise::serenity_prelude::{
-    self as serenity, ChannelId, Colour, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId,
-    Message, MessageId, MessageUpdateEvent, UserId,
+    self as serenity, ChannelId, Colour, CreateEmbedFooter, GuildId, Message, MessageId,
+    MessageUpdateEvent, UserId,
 };
 
 pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) -> Result<(), Error> {
-    let (content, patterns) = {
+    let (scan, skip, patterns) = {
         let config = &data.config.read().events;
 
-        if should_skip_msg(
+        let lists = config.filter_lists_for(msg.guild_id);
+        let scan = moth_filter::scan(&msg.content, lists);
+        let skip = should_skip_msg(
             config.no_log_users.as_ref(),
             config.no_log_channels.as_ref(),
             msg,
-        ) {
-            return Ok(());
-        }
+        );
 
-        let maybe_flagged =
-            moth_filter::filter_content(&msg.content, &config.badlist, &config.fixlist);
+        (scan, skip, config.regex.clone())
+    };
 
-        (maybe_flagged, config.regex.clone())
+    // moderation has its own exemptions, so it still runs for messages that aren't logged.
+    let moderation = async {
+        tokio::join!(
+            moderate_invites(ctx, &data, msg),
+            detect_spam(ctx, &data, msg),
+            moderate_filter(ctx, &data, msg, &scan),
+        )
     };
 
+    if skip {
+        let _ = moderation.await;
+        return Ok(());
+    }
+
+    let content = moth_filter::highlight(&msg.content, &scan);
+
     let guild_id = msg.guild_id;
     let guild_name = get_guild_name_override(ctx, &data, guild_id);
     let channel_name = get_channel_name(ctx, guild_id, msg.channel_id).await;
@@ -54,11 +73,12 @@ pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) ->
     let guild_name = get_guild_name(ctx, guild_id);
     let _ = tokio::join!(
         data.check_or_insert_user(&msg.author),
+        data.check_or_insert_avatars(&msg.author),
         maybe_names(&data, msg.author.id, msg.guild_id, msg.member.as_ref()),
         check_event_dm_regex(ctx, msg, &guild_name, patterns.as_deref()),
         handle_dm(ctx, msg),
         insert_message(&data.database, msg),
-        moderate_invites(ctx, &data, msg),
+        moderation,
     );
 
     Ok(())
@@ -74,6 +94,12 @@ async fn maybe_names(
         if let Some(nick) = member.nick.as_ref().map(std::string::ToString::to_string) {
             data.check_or_insert_nick(id, author_id, Some(nick)).await;
         }
+
+        // messages only carry a guild avatar when one is set, so a missing one means nothing.
+        if let Some(avatar) = &member.avatar {
+            data.check_or_insert_guild_avatar(id, author_id, Some(avatar))
+                .await;
+        }
     }
 }
 
@@ -148,11 +174,11 @@ pub async fn message_delete(
         .message(*channel_id, *deleted_message_id)
         .map(|message_ref| message_ref.clone());
 
-    if let Some(message) = message {
+    if let Some(message) = &message {
         let user_name = message.author.tag();
         let content = message.content.clone();
 
-        let (attachments_fmt, embeds_fmt) = attachments_embed_fmt(&message);
+        let (attachments_fmt, embeds_fmt) = attachments_embed_fmt(message);
 
         println!(
             "{HI_RED}{DIM}[{}] [#{}] A message from {RESET}{}{HI_RED}{DIM} was deleted: \
@@ -165,7 +191,7 @@ pub async fn message_delete(
             embeds_fmt.as_deref().unwrap_or("")
         );
 
-        let _ = insert_deletion(&data.database, &message).await;
+        let _ = insert_deletion(&data.database, message).await;
     } else {
         println!(
             "{HI_RED}{DIM}A message (ID:{deleted_message_id}) was deleted but was not in \
@@ -174,22 +200,16 @@ pub async fn message_delete(
     }
 
     if let Some(guild_id) = guild_id {
-        if let Some(user) =
-            anti_delete::anti_delete(ctx, &data, channel_id, guild_id, deleted_message_id).await
-        {
-            if guild_id.get() == 98226572468690944 {
-                let embed = CreateEmbed::new()
-                    .title("Possible mass deletion?")
-                    .description(format!("Triggered on <@{user}>"))
-                    .footer(CreateEmbedFooter::new(
-                        "This doesn't check my own database or oinks database.",
-                    ));
-                let builder = CreateMessage::new().embed(embed);
-                let _ = ChannelId::new(1284217769423798282)
-                    .send_message(&ctx.http, builder)
-                    .await;
-            };
-        }
+        let author = message.as_ref().map(|m| m.author.id);
+        anti_delete::anti_delete(
+            ctx,
+            &data,
+            *channel_id,
+            *guild_id,
+            *deleted_message_id,
+            author,
+        )
+        .await?;
     }
 
     Ok(())
@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::helper::get_guild_name_override;
+use crate::moderation::{guild_config, has_any_role, log_to, timeout_member, warn_user};
+use crate::{Data, Error};
+use moth_ansi::{HI_RED, RESET};
+use moth_config::{ModerationAction, SpamDetection};
+use moth_data::structs::{SeenMessage, SpamWindow};
+use moth_filter::is_invisible;
+use poise::serenity_prelude::{
+    self as serenity, Attachment, ChannelId, CreateAllowedMentions, CreateMessage, GuildId,
+    Message, MessageId, UserId,
+};
+
+const REASON: &str = "Posted the same message repeatedly";
+
+/// Attachments larger than this are hashed by their name and size instead of downloaded.
+const MAX_HASHED_ATTACHMENT: u32 = 8 * 1024 * 1024;
+
+async fn download(data: &Data, attachment: &Attachment) -> Result<Vec<u8>, Error> {
+    let bytes = data
+        .reqwest
+        .get(attachment.url.as_str())
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    Ok(bytes.to_vec())
+}
+
+/// Hashes the content of a message with whitespace and case removed, along with the contents of
+/// every attachment, so the same file posted under another name still matches.
+///
+/// Attachments too large to download, or that fail to, fall back to their name and size.
+///
+/// Returns the fingerprint and how substantial the message is, or `None` if it is empty.
+async fn fingerprint(data: &Data, msg: &Message) -> Option<(u64, usize)> {
+    let content = msg
+        .content
+        .chars()
+        .filter(|c| !c.is_whitespace() && !is_invisible(*c))
+        .flat_map(char::to_lowercase)
+        .collect::<String>();
+
+    if content.is_empty() && msg.attachments.is_empty() {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    for attachment in &msg.attachments {
+        let bytes = if attachment.size <= MAX_HASHED_ATTACHMENT {
+            download(data, attachment)
+                .await
+                .inspect_err(|e| println!("Failed to download {}: {e}", attachment.url))
+                .ok()
+        } else {
+            None
+        };
+
+        if let Some(bytes) = bytes {
+            bytes.hash(&mut hasher);
+        } else {
+            attachment.filename.hash(&mut hasher);
+            attachment.size.hash(&mut hasher);
+        }
+    }
+
+    // attachments are always worth comparing.
+    let length = if msg.attachments.is_empty() {
+        content.chars().count()
+    } else {
+        usize::MAX
+    };
+
+    Some((hasher.finish(), length))
+}
+
+enum Spam {
+    /// One user posted the same message too often or in too many channels.
+    Repeated { user_id: UserId, channels: usize },
+    /// Several users posted the same message, which usually means their accounts were taken over.
+    Compromised { users: Vec<UserId> },
+}
+
+struct Detection {
+    spam: Spam,
+    /// The copies to act on, only the newest message if the spam was already alerted on.
+    copies: Vec<(UserId, ChannelId, MessageId)>,
+    first: bool,
+}
+
+fn check(
+    window: &mut SpamWindow,
+    config: &SpamDetection,
+    fingerprint: u64,
+    user_id: UserId,
+    length: usize,
+) -> Option<Detection> {
+    let matching = window
+        .messages
+        .iter()
+        .filter(|m| m.fingerprint == fingerprint)
+        .collect::<Vec<_>>();
+
+    let own = matching
+        .iter()
+        .filter(|m| m.user_id == user_id)
+        .collect::<Vec<_>>();
+    let channels = own
+        .iter()
+        .map(|m| m.channel_id)
+        .collect::<HashSet<_>>()
+        .len();
+
+    let users = matching.iter().map(|m| m.user_id).collect::<HashSet<_>>();
+
+    let (spam, key, copies) =
+        if channels >= config.channel_threshold || own.len() >= config.repeat_threshold {
+            let copies = own
+                .iter()
+                .map(|m| (m.user_id, m.channel_id, m.message_id))
+                .collect::<Vec<_>>();
+            (Spam::Repeated { user_id, channels }, Some(user_id), copies)
+        } else if users.len() >= config.user_threshold && length >= config.min_length {
+            let copies = matching
+                .iter()
+                .map(|m| (m.user_id, m.channel_id, m.message_id))
+                .collect::<Vec<_>>();
+            let users = users.into_iter().collect();
+            (Spam::Compromised { users }, None, copies)
+        } else {
+            return None;
+        };
+
+    let first = !window.fired.contains_key(&(fingerprint, key));
+    window.fired.insert((fingerprint, key), Instant::now());
+
+    // copies from before the alert have already been acted on.
+    let copies = if first {
+        copies
+    } else {
+        copies.into_iter().last().into_iter().collect()
+    };
+
+    Some(Detection {
+        spam,
+        copies,
+        first,
+    })
+}
+
+/// Fingerprints messages per guild, acting when the same message is posted across channels or
+/// by several users within the configured window.
+pub async fn detect_spam(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() || msg.webhook_id.is_some() {
+        return Ok(());
+    }
+
+    let Some(config) = guild_config(data, guild_id, |e| e.spam_detection.as_ref()) else {
+        return Ok(());
+    };
+
+    if config.exempt_channels.contains(&msg.channel_id) || has_any_role(msg, &config.bypass_roles) {
+        return Ok(());
+    }
+
+    let Some((fingerprint, length)) = fingerprint(data, msg).await else {
+        return Ok(());
+    };
+
+    let detection = {
+        let mut window = data.spam_cache.guilds.entry(guild_id).or_default();
+        window.prune(Duration::from_secs(config.window_secs));
+        window.messages.push_back(SeenMessage {
+            fingerprint,
+            user_id: msg.author.id,
+            channel_id: msg.channel_id,
+            message_id: msg.id,
+            seen: Instant::now(),
+        });
+
+        check(&mut window, &config, fingerprint, msg.author.id, length)
+    };
+
+    let Some(detection) = detection else {
+        return Ok(());
+    };
+
+    let action = act(ctx, &config, guild_id, &detection.copies).await;
+
+    if !detection.first {
+        return Ok(());
+    }
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+    let description = match &detection.spam {
+        Spam::Repeated { user_id, channels } => format!(
+            "<@{user_id}> posted the same message {} times in {channels} channels",
+            detection.copies.len()
+        ),
+        Spam::Compromised { users } => format!(
+            "{} users posted the same message, their accounts may be compromised: {}",
+            users.len(),
+            users
+                .iter()
+                .map(|u| format!("<@{u}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+
+    println!(
+        "{HI_RED}[{guild_name}] Spam detected from {}{RESET}",
+        msg.author.tag()
+    );
+
+    let Some(alert_channel) = config.alert_channel else {
+        return Ok(());
+    };
+
+    let mut channels = detection
+        .copies
+        .iter()
+        .map(|(_, channel_id, _)| *channel_id)
+        .collect::<Vec<_>>();
+    channels.sort_unstable();
+    channels.dedup();
+    let channels = channels
+        .iter()
+        .map(|c| format!("<#{c}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let preview = msg.content.chars().take(500).collect::<String>();
+    let action = if config.action == ModerationAction::Log {
+        String::new()
+    } else {
+        format!("\n**Action:** {action}")
+    };
+
+    let content = format!("{description} in {channels}{action}\n>>> {preview}");
+    let builder = CreateMessage::new().content(content).allowed_mentions(
+        CreateAllowedMentions::new()
+            .all_users(false)
+            .all_roles(false)
+            .everyone(false),
+    );
+    log_to(ctx, alert_channel, builder).await;
+
+    Ok(())
+}
+
+/// Deletes every copy and warns or times out their authors, depending on the configured action.
+async fn act(
+    ctx: &serenity::Context,
+    config: &SpamDetection,
+    guild_id: GuildId,
+    copies: &[(UserId, ChannelId, MessageId)],
+) -> String {
+    if config.action == ModerationAction::Log {
+        return String::from("Logged");
+    }
+
+    let mut deleted = 0;
+    for (_, channel_id, message_id) in copies {
+        match channel_id
+            .delete_message(&ctx.http, *message_id, Some(REASON))
+            .await
+        {
+            Ok(()) => deleted += 1,
+            Err(e) => println!("Failed to delete message {message_id}: {e}"),
+        }
+    }
+
+    let mut action = format!("Deleted {deleted}/{} copies", copies.len());
+
+    let mut users = copies.iter().map(|(u, _, _)| *u).collect::<Vec<_>>();
+    users.sort_unstable();
+    users.dedup();
+
+    if config.action == ModerationAction::Warn {
+        let mut warned = 0;
+        for user_id in &users {
+            let warning = format!("Your messages were removed: {REASON}.");
+            if warn_user(ctx, *user_id, warning).await {
+                warned += 1;
+            }
+        }
+
+        action = format!("{action}, warned {warned}/{} users", users.len());
+    }
+
+    if config.action == ModerationAction::DeleteAndTimeout {
+        let duration = Duration::from_secs(config.timeout_secs);
+        let mut timed_out = 0;
+        for user_id in &users {
+            if timeout_member(ctx, guild_id, *user_id, duration, REASON).await {
+                timed_out += 1;
+            }
+        }
+
+        action = format!(
+            "{action}, timed out {timed_out}/{} users for {}s",
+            users.len(),
+            duration.as_secs()
+        );
+    }
+
+    action
+}
@@ -18,21 +18,38 @@ pub async fn ready(ctx: &serenity::Context, ready: &Ready, data: Arc<Data>) -> R
     let is_last_shard = (ctx.shard_id.0 + 1) == shard_count.get();
 
     if is_last_shard && !data.has_started.swap(true, Ordering::SeqCst) {
-        finalize_start(&data);
+        finalize_start(ctx, &data);
         println!("Logged in as {}", ready.user.tag());
     }
 
     Ok(())
 }
 
-fn finalize_start(data: &Arc<Data>) {
+fn finalize_start(ctx: &serenity::Context, data: &Arc<Data>) {
+    let ctx = ctx.clone();
     let data_clone = data.clone();
 
     tokio::spawn(async move {
         let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(2));
         loop {
             interval.tick().await;
-            data_clone.anti_delete_cache.decay_proc();
+            super::messages::finish_bursts(&ctx, &data_clone).await;
+            super::raids::finish_raids(&ctx, &data_clone).await;
+            data_clone.vc_status_correlation.prune();
+            data_clone.voice_moderation_correlation.prune();
+        }
+    });
+
+    let ctx = ctx.clone();
+    let data_clone = data.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = super::voice::touch_voice_sessions(&ctx, &data_clone).await {
+                println!("Failed to update voice sessions: {e}");
+            }
         }
     });
 }
@@ -1,7 +1,13 @@
 pub mod channels;
+pub mod expressions;
 pub mod guilds;
+pub mod invites;
 pub mod messages;
 pub mod misc;
+pub mod name_filter;
+pub mod raids;
 pub mod reactions;
+pub mod roles;
 pub mod users;
+pub mod vc_status;
 pub mod voice;
@@ -0,0 +1,507 @@
+//! Runs names, statuses and thread titles through the same filter as messages.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::helper::get_guild_name_override;
+use crate::moderation::{guild_config, log_to, timeout_member};
+use crate::{Data, Error};
+use moth_ansi::{HI_RED, RESET};
+use moth_config::{MinSeverity, NameFilterAction, NameFiltering, VcStatusFilterAction};
+use moth_filter::Severity;
+use poise::serenity_prelude::{
+    self as serenity, ActivityType, ChannelId, CreateAllowedMentions, CreateEmbed, CreateMessage,
+    EditMember, EditThread, GuildChannel, GuildId, Member, Presence, RoleId, UserId,
+};
+
+const REASON: &str = "Flagged by the name filter";
+
+fn meets(severity: Severity, min_severity: MinSeverity) -> bool {
+    let severity = match severity {
+        Severity::Mild => MinSeverity::Mild,
+        Severity::Moderate => MinSeverity::Moderate,
+        Severity::Severe => MinSeverity::Severe,
+    };
+    severity >= min_severity
+}
+
+/// Runs text through the guilds filter, returning why it was flagged.
+fn flag_reason(
+    data: &Data,
+    config: &NameFiltering,
+    guild_id: GuildId,
+    text: &str,
+) -> Option<String> {
+    let scan = {
+        let events = &data.config.read().events;
+        let lists = events.filter_lists_for(Some(guild_id));
+        moth_filter::scan(text, lists)
+    };
+
+    let mut reasons = Vec::new();
+    if let Some(severity) = scan.severity().filter(|s| meets(*s, config.min_severity)) {
+        reasons.push(format!("{severity:?} severity"));
+    }
+    if !scan.badlist_words.is_empty() {
+        let words = scan
+            .badlist_words
+            .iter()
+            .map(|w| format!("`{w}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        reasons.push(format!("badlist: {words}"));
+    }
+
+    (!reasons.is_empty()).then(|| reasons.join(", "))
+}
+
+/// Checks if a cached member has any of the given roles.
+fn is_bypassed(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    roles: &[RoleId],
+) -> bool {
+    if roles.is_empty() {
+        return false;
+    }
+
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return false;
+    };
+    guild
+        .members
+        .get(&user_id)
+        .is_some_and(|m| m.roles.iter().any(|r| roles.contains(r)))
+}
+
+/// Something the filter flagged, and what was done about it.
+struct Flagged<'a> {
+    scope: &'static str,
+    guild_id: GuildId,
+    user_id: Option<UserId>,
+    channel_id: Option<ChannelId>,
+    text: &'a str,
+    reason: String,
+    taken: String,
+}
+
+async fn report(
+    ctx: &serenity::Context,
+    data: &Data,
+    config: &NameFiltering,
+    flagged: Flagged<'_>,
+) {
+    let guild_name = get_guild_name_override(ctx, data, Some(flagged.guild_id));
+    println!(
+        "{HI_RED}[{guild_name}] A {} was flagged by the filter: {} ({}){RESET}",
+        flagged.scope.to_lowercase(),
+        flagged.text,
+        flagged.taken
+    );
+
+    let Some(log_channel) = config.log_channel else {
+        return;
+    };
+
+    let mut description = String::new();
+    if let Some(user_id) = flagged.user_id {
+        writeln!(description, "**User:** <@{user_id}>").unwrap();
+    }
+    if let Some(channel_id) = flagged.channel_id {
+        writeln!(description, "**Channel:** <#{channel_id}>").unwrap();
+    }
+    write!(
+        description,
+        "**Reason:** {}\n**Action:** {}\n\n>>> {}",
+        flagged.reason,
+        flagged.taken,
+        flagged.text.chars().take(1000).collect::<String>()
+    )
+    .unwrap();
+
+    let embed = CreateEmbed::new()
+        .title(format!("Filtered {}", flagged.scope.to_lowercase()))
+        .description(description);
+    let builder = CreateMessage::new().embed(embed).allowed_mentions(
+        CreateAllowedMentions::new()
+            .all_users(false)
+            .all_roles(false)
+            .everyone(false),
+    );
+    log_to(ctx, log_channel, builder).await;
+}
+
+/// Times out the user if the action calls for it, describing what was done.
+async fn maybe_timeout(
+    ctx: &serenity::Context,
+    config: &NameFiltering,
+    action: NameFilterAction,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Option<String> {
+    if action != NameFilterAction::Timeout {
+        return None;
+    }
+
+    let duration = Duration::from_secs(config.timeout_secs);
+    Some(
+        if timeout_member(ctx, guild_id, user_id, duration, REASON).await {
+            format!("timed out for {}s", duration.as_secs())
+        } else {
+            String::from("failed to time out")
+        },
+    )
+}
+
+async fn set_nickname(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    nickname: &str,
+) -> bool {
+    let builder = EditMember::new()
+        .nickname(nickname)
+        .audit_log_reason(REASON);
+    match guild_id.edit_member(&ctx.http, user_id, builder).await {
+        Ok(_) => true,
+        Err(e) => {
+            println!("Failed to change the nickname of {user_id}: {e}");
+            false
+        }
+    }
+}
+
+/// The names a member had before an update, `None` where they aren't known.
+pub struct PreviousNames {
+    pub nick: Option<Option<String>>,
+    pub global_name: Option<Option<String>>,
+}
+
+impl PreviousNames {
+    /// Takes the names of the old member, or of the names cache when the member wasn't cached.
+    ///
+    /// This has to be called before the names cache is updated with the new names.
+    #[must_use]
+    pub fn new(data: &Data, old: Option<&Member>, guild_id: GuildId, user_id: UserId) -> Self {
+        if let Some(old) = old {
+            return PreviousNames {
+                nick: Some(old.nick.as_ref().map(ToString::to_string)),
+                global_name: Some(old.user.global_name.as_ref().map(ToString::to_string)),
+            };
+        }
+
+        PreviousNames {
+            nick: data.cached_nick(guild_id, user_id),
+            global_name: data.cached_global_name(user_id),
+        }
+    }
+}
+
+/// Checks the nickname and global name of a member when either changes, returning if their
+/// nickname was changed.
+pub async fn check_member(
+    ctx: &serenity::Context,
+    data: &Data,
+    previous: &PreviousNames,
+    new: &Member,
+) -> Result<bool, Error> {
+    let guild_id = new.guild_id;
+    let user_id = new.user.id;
+
+    if new.user.bot() {
+        return Ok(false);
+    }
+
+    let Some(config) = guild_config(data, guild_id, |e| e.name_filtering.as_ref()) else {
+        return Ok(false);
+    };
+
+    if new.roles.iter().any(|r| config.bypass_roles.contains(r)) {
+        return Ok(false);
+    }
+
+    // names that were never seen are checked, anything else only when it changed.
+    let nick_changed = previous
+        .nick
+        .as_ref()
+        .is_none_or(|n| n.as_deref() != new.nick.as_deref());
+    let global_changed = previous
+        .global_name
+        .as_ref()
+        .is_none_or(|n| n.as_deref() != new.user.global_name.as_deref());
+
+    let nick_reason = new
+        .nick
+        .as_deref()
+        .filter(|_| nick_changed && config.nicknames != NameFilterAction::None)
+        .and_then(|nick| flag_reason(data, &config, guild_id, nick).map(|r| (nick, r)));
+
+    if let Some((nick, reason)) = nick_reason {
+        let mut taken = vec![String::from("Logged")];
+        let mut renamed = false;
+
+        if config.nicknames >= NameFilterAction::Reset {
+            // the global name shows once the nickname is removed, so it needs checking too.
+            let global_flagged = new
+                .user
+                .global_name
+                .as_deref()
+                .is_some_and(|name| flag_reason(data, &config, guild_id, name).is_some());
+            let nickname = if global_flagged {
+                config.fallback_name.as_str()
+            } else {
+                ""
+            };
+
+            renamed = set_nickname(ctx, guild_id, user_id, nickname).await;
+            taken = vec![if renamed {
+                String::from("Reset")
+            } else {
+                String::from("Failed to reset")
+            }];
+        }
+
+        taken.extend(maybe_timeout(ctx, &config, config.nicknames, guild_id, user_id).await);
+
+        let flagged = Flagged {
+            scope: "Nickname",
+            guild_id,
+            user_id: Some(user_id),
+            channel_id: None,
+            text: nick,
+            reason,
+            taken: taken.join(", "),
+        };
+        report(ctx, data, &config, flagged).await;
+        return Ok(renamed);
+    }
+
+    let global_reason = new
+        .user
+        .global_name
+        .as_deref()
+        .filter(|_| global_changed && config.global_names != NameFilterAction::None)
+        .and_then(|name| flag_reason(data, &config, guild_id, name).map(|r| (name, r)));
+
+    let Some((name, reason)) = global_reason else {
+        return Ok(false);
+    };
+
+    let mut taken = vec![String::from("Logged")];
+    let mut renamed = false;
+
+    // a nickname hides the global name, so it only needs covering when there isn't one.
+    if config.global_names >= NameFilterAction::Reset && new.nick.is_none() {
+        renamed = set_nickname(ctx, guild_id, user_id, &config.fallback_name).await;
+        taken = vec![if renamed {
+            String::from("Covered with the fallback nickname")
+        } else {
+            String::from("Failed to set the fallback nickname")
+        }];
+    }
+
+    taken.extend(maybe_timeout(ctx, &config, config.global_names, guild_id, user_id).await);
+
+    let flagged = Flagged {
+        scope: "Global name",
+        guild_id,
+        user_id: Some(user_id),
+        channel_id: None,
+        text: name,
+        reason,
+        taken: taken.join(", "),
+    };
+    report(ctx, data, &config, flagged).await;
+
+    Ok(renamed)
+}
+
+/// Checks a voice channel status when it is set.
+pub async fn check_vc_status(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    status: Option<&str>,
+) -> Result<(), Error> {
+    let Some(status) = status.filter(|s| !s.is_empty()) else {
+        return Ok(());
+    };
+
+    let Some(config) = guild_config(data, guild_id, |e| e.name_filtering.as_ref()) else {
+        return Ok(());
+    };
+
+    if config.vc_statuses == VcStatusFilterAction::None {
+        return Ok(());
+    }
+
+    let Some(reason) = flag_reason(data, &config, guild_id, status) else {
+        return Ok(());
+    };
+
+    let mut taken = String::from("Logged");
+    if config.vc_statuses == VcStatusFilterAction::Reset {
+        let map = serde_json::json!({ "status": "" });
+        taken = match ctx
+            .http
+            .edit_voice_status(channel_id, &map, Some(REASON))
+            .await
+        {
+            Ok(()) => String::from("Cleared"),
+            Err(e) => {
+                println!("Failed to clear the status of {channel_id}: {e}");
+                String::from("Failed to clear")
+            }
+        };
+    }
+
+    let flagged = Flagged {
+        scope: "Voice channel status",
+        guild_id,
+        user_id: None,
+        channel_id: Some(channel_id),
+        text: status,
+        reason,
+        taken,
+    };
+    report(ctx, data, &config, flagged).await;
+
+    Ok(())
+}
+
+/// Checks the title of a thread or forum post when it is created or renamed.
+pub async fn check_thread(
+    ctx: &serenity::Context,
+    data: &Data,
+    old: Option<&GuildChannel>,
+    thread: &GuildChannel,
+) -> Result<(), Error> {
+    if old.is_some_and(|o| o.name == thread.name) {
+        return Ok(());
+    }
+
+    let guild_id = thread.guild_id;
+    let Some(config) = guild_config(data, guild_id, |e| e.name_filtering.as_ref()) else {
+        return Ok(());
+    };
+
+    if config.thread_titles == NameFilterAction::None {
+        return Ok(());
+    }
+
+    if thread
+        .owner_id
+        .is_some_and(|o| is_bypassed(ctx, guild_id, o, &config.bypass_roles))
+    {
+        return Ok(());
+    }
+
+    let Some(reason) = flag_reason(data, &config, guild_id, &thread.name) else {
+        return Ok(());
+    };
+
+    let mut taken = vec![String::from("Logged")];
+    if config.thread_titles >= NameFilterAction::Reset {
+        // renames can be undone, new threads have nothing to go back to so they are locked.
+        let old_name = old
+            .map(|o| o.name.as_str())
+            .filter(|name| flag_reason(data, &config, guild_id, name).is_none());
+
+        let (builder, action) = match old_name {
+            Some(name) => (EditThread::new().name(name), "Renamed back"),
+            None => (EditThread::new().locked(true).archived(true), "Locked"),
+        };
+
+        taken = vec![match thread
+            .id
+            .edit_thread(&ctx.http, builder.audit_log_reason(REASON))
+            .await
+        {
+            Ok(_) => String::from(action),
+            Err(e) => {
+                println!("Failed to edit thread {}: {e}", thread.id);
+                String::from("Failed to reset")
+            }
+        }];
+    }
+
+    if let Some(owner_id) = thread.owner_id {
+        taken.extend(maybe_timeout(ctx, &config, config.thread_titles, guild_id, owner_id).await);
+    }
+
+    let flagged = Flagged {
+        scope: "Thread title",
+        guild_id,
+        user_id: thread.owner_id,
+        channel_id: Some(thread.id),
+        text: &thread.name,
+        reason,
+        taken: taken.join(", "),
+    };
+    report(ctx, data, &config, flagged).await;
+
+    Ok(())
+}
+
+fn custom_status(presence: &Presence) -> Option<&str> {
+    presence
+        .activities
+        .iter()
+        .find(|a| a.kind == ActivityType::Custom)
+        .and_then(|a| a.state.as_deref())
+        .filter(|s| !s.is_empty())
+}
+
+/// Checks custom statuses when they change.
+pub async fn check_presence(
+    ctx: &serenity::Context,
+    data: &Data,
+    old: Option<&Presence>,
+    new: &Presence,
+) -> Result<(), Error> {
+    let Some(guild_id) = new.guild_id else {
+        return Ok(());
+    };
+
+    let Some(status) = custom_status(new) else {
+        return Ok(());
+    };
+
+    // presences update for plenty of other reasons.
+    if old.and_then(custom_status) == Some(status) {
+        return Ok(());
+    }
+
+    let Some(config) = guild_config(data, guild_id, |e| e.name_filtering.as_ref()) else {
+        return Ok(());
+    };
+
+    let user_id = new.user.id;
+    if config.custom_statuses == NameFilterAction::None
+        || is_bypassed(ctx, guild_id, user_id, &config.bypass_roles)
+    {
+        return Ok(());
+    }
+
+    let Some(reason) = flag_reason(data, &config, guild_id, status) else {
+        return Ok(());
+    };
+
+    let mut taken = vec![String::from("Logged")];
+    taken.extend(maybe_timeout(ctx, &config, config.custom_statuses, guild_id, user_id).await);
+
+    let flagged = Flagged {
+        scope: "Custom status",
+        guild_id,
+        user_id: Some(user_id),
+        channel_id: None,
+        text: status,
+        reason,
+        taken: taken.join(", "),
+    };
+    report(ctx, data, &config, flagged).await;
+
+    Ok(())
+}
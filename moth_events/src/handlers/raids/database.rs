@@ -0,0 +1,65 @@
+use chrono::Utc;
+use poise::serenity_prelude::GuildId;
+use sqlx::query;
+
+use crate::Error;
+
+use moth_data::database::{Database, SuspectAction};
+use moth_data::structs::SuspectJoin;
+
+pub(super) async fn insert_raid(
+    database: &Database,
+    guild_id: GuildId,
+    previous_verification_level: Option<i16>,
+) -> Result<i32, Error> {
+    database.insert_guild(guild_id).await?;
+
+    let record = query!(
+        "INSERT INTO raids (guild_id, started_at, previous_verification_level) VALUES ($1, $2, \
+         $3) RETURNING id",
+        guild_id.get() as i64,
+        Utc::now().naive_utc(),
+        previous_verification_level
+    )
+    .fetch_one(&database.db)
+    .await?;
+
+    Ok(record.id)
+}
+
+pub(super) async fn insert_suspects(
+    database: &Database,
+    raid_id: i32,
+    suspects: &[(SuspectJoin, SuspectAction)],
+) -> Result<(), Error> {
+    for (join, action) in suspects {
+        database.insert_user(join.user_id).await?;
+
+        query!(
+            "INSERT INTO raid_suspects (raid_id, user_id, score, signals, action, joined_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (raid_id, user_id) DO NOTHING",
+            raid_id,
+            join.user_id.get() as i64,
+            join.score() as i16,
+            join.signals(),
+            *action as SuspectAction,
+            join.joined_at
+        )
+        .execute(&database.db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub(super) async fn end_raid(database: &Database, raid_id: i32) -> Result<(), Error> {
+    query!(
+        "UPDATE raids SET ended_at = $1 WHERE id = $2",
+        Utc::now().naive_utc(),
+        raid_id
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,277 @@
+mod database;
+
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::helper::get_guild_name_override;
+use crate::moderation::{guild_config, log_to, timeout_member};
+use crate::{Data, Error};
+use database::{end_raid, insert_raid, insert_suspects};
+
+use chrono::Utc;
+use moth_ansi::{HI_RED, RESET};
+use moth_config::{RaidAction, RaidDetection};
+use moth_data::database::SuspectAction;
+use moth_data::structs::{JoinBurst, SuspectJoin};
+use poise::serenity_prelude::{
+    self as serenity, CreateEmbed, CreateEmbedFooter, CreateMessage, EditGuild, GuildId, Member,
+    VerificationLevel,
+};
+
+const REASON: &str = "Joined during a raid";
+
+/// How many of the latest joins a name is compared against, this happens while the burst is
+/// locked.
+const SIMILARITY_WINDOW: usize = 100;
+
+fn suspect_join(config: &RaidDetection, member: &Member) -> SuspectJoin {
+    let user = &member.user;
+    let age = Utc::now().timestamp() - user.id.created_at().unix_timestamp();
+
+    SuspectJoin {
+        user_id: user.id,
+        name: user.name.to_string(),
+        joined_at: Utc::now().naive_utc(),
+        new_account: age < (config.new_account_days * 86400) as i64,
+        default_avatar: user.avatar.is_none(),
+        similar_name: false,
+    }
+}
+
+/// Adds a join to the guilds burst, starting a raid response once the heat crosses the threshold.
+pub async fn raid_check(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    member: &Member,
+) -> Result<(), Error> {
+    let guild_id = member.guild_id;
+    let Some(config) = guild_config(data, guild_id, |e| e.raid_detection.as_ref()) else {
+        return Ok(());
+    };
+
+    let mut join = suspect_join(&config, member);
+
+    let (raid_id, triggered) = {
+        let mut entry = data
+            .raid_cache
+            .bursts
+            .entry(guild_id)
+            .or_insert_with(JoinBurst::new);
+        let burst = &mut *entry;
+
+        let name = join.name.to_lowercase();
+        for other in burst.joins.iter_mut().rev().take(SIMILARITY_WINDOW) {
+            let similarity = strsim::normalized_levenshtein(&other.name.to_lowercase(), &name);
+            if similarity < config.name_similarity {
+                continue;
+            }
+
+            join.similar_name = true;
+            if !other.similar_name {
+                other.similar_name = true;
+                burst.heat += 1;
+            }
+        }
+
+        burst.heat += join.score();
+        burst.last_update = Instant::now();
+        burst.joins.push(join.clone());
+
+        let triggered = !burst.raided && burst.heat >= config.threshold;
+        burst.raided |= triggered;
+        (burst.raid_id, triggered)
+    };
+
+    if let Some(raid_id) = raid_id {
+        // the raid is already ongoing, so this join is responded to on its own.
+        let action = respond(ctx, &config, guild_id, &join).await;
+        insert_suspects(&data.database, raid_id, &[(join, action)]).await?;
+        return Ok(());
+    }
+
+    if triggered {
+        start_raid(ctx, data, &config, guild_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn start_raid(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    config: &RaidDetection,
+    guild_id: GuildId,
+) -> Result<(), Error> {
+    let raised = if config.raise_verification {
+        raise_verification(ctx, guild_id).await
+    } else {
+        None
+    };
+
+    // a level raised by an earlier attempt that failed to store the raid is still the one to
+    // restore, the current level is already high.
+    let previous_level = match data.raid_cache.bursts.get_mut(&guild_id) {
+        Some(mut burst) => {
+            burst.previous_verification = burst.previous_verification.or(raised);
+            burst.previous_verification
+        }
+        None => raised,
+    };
+
+    let raid_id = match insert_raid(&data.database, guild_id, previous_level).await {
+        Ok(raid_id) => raid_id,
+        Err(e) => {
+            // lets the next join try again.
+            if let Some(mut burst) = data.raid_cache.bursts.get_mut(&guild_id) {
+                burst.raided = false;
+            }
+            return Err(e);
+        }
+    };
+
+    let joins = {
+        let Some(mut burst) = data.raid_cache.bursts.get_mut(&guild_id) else {
+            // the burst cooled down while the raid was stored, so nothing would end it.
+            end_raid(&data.database, raid_id).await?;
+            return Ok(());
+        };
+        burst.raid_id = Some(raid_id);
+        burst.joins.clone()
+    };
+
+    let mut suspects = Vec::with_capacity(joins.len());
+    for join in joins {
+        let action = respond(ctx, config, guild_id, &join).await;
+        suspects.push((join, action));
+    }
+
+    insert_suspects(&data.database, raid_id, &suspects).await?;
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+    println!(
+        "{HI_RED}[{guild_name}] Possible raid, {} members joined in a burst{RESET}",
+        suspects.len()
+    );
+
+    let Some(alert_channel) = config.alert_channel else {
+        return Ok(());
+    };
+
+    let mut description = String::new();
+    if previous_level.is_some() {
+        description.push_str("The verification level was raised to high.\n\n");
+    }
+
+    for (join, action) in suspects.iter().take(25) {
+        let signals = join.signals();
+        let signals = if signals.is_empty() {
+            String::from("no signals")
+        } else {
+            signals
+        };
+        writeln!(
+            description,
+            "<@{}> `{}`: {signals} ({})",
+            join.user_id,
+            join.name,
+            action.describe()
+        )
+        .unwrap();
+    }
+
+    if suspects.len() > 25 {
+        writeln!(description, "...and {} more", suspects.len() - 25).unwrap();
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Possible raid")
+        .description(description.trim_end_matches('\n'))
+        .footer(CreateEmbedFooter::new(format!(
+            "Raid #{raid_id}, review or undo it with the raid command."
+        )));
+    log_to(ctx, alert_channel, CreateMessage::new().embed(embed)).await;
+
+    Ok(())
+}
+
+/// Raises the verification level to high, returning the previous level if it was raised.
+async fn raise_verification(ctx: &serenity::Context, guild_id: GuildId) -> Option<i16> {
+    let current = ctx.cache.guild(guild_id).map(|g| g.verification_level)?;
+    if u8::from(current) >= u8::from(VerificationLevel::High) {
+        return None;
+    }
+
+    let builder = EditGuild::new()
+        .verification_level(VerificationLevel::High)
+        .audit_log_reason("Raid detected");
+
+    match guild_id.edit(&ctx.http, builder).await {
+        Ok(_) => Some(i16::from(u8::from(current))),
+        Err(e) => {
+            println!("Failed to raise the verification level of {guild_id}: {e}");
+            None
+        }
+    }
+}
+
+async fn respond(
+    ctx: &serenity::Context,
+    config: &RaidDetection,
+    guild_id: GuildId,
+    join: &SuspectJoin,
+) -> SuspectAction {
+    match config.action {
+        RaidAction::None => SuspectAction::None,
+        RaidAction::Timeout => {
+            let duration = Duration::from_secs(config.timeout_secs);
+            if timeout_member(ctx, guild_id, join.user_id, duration, REASON).await {
+                SuspectAction::TimedOut
+            } else {
+                SuspectAction::TimeoutFailed
+            }
+        }
+        RaidAction::Kick => match guild_id.kick(&ctx.http, join.user_id, Some(REASON)).await {
+            Ok(()) => SuspectAction::Kicked,
+            Err(e) => {
+                println!("Failed to kick {}: {e}", join.user_id);
+                SuspectAction::KickFailed
+            }
+        },
+    }
+}
+
+/// Decays the join heat of every guild, ending the raids of bursts that have stopped.
+pub async fn finish_raids(ctx: &serenity::Context, data: &Arc<Data>) {
+    let configs = data
+        .config
+        .read()
+        .events
+        .raid_detection
+        .clone()
+        .unwrap_or_default();
+
+    let finished = data.raid_cache.decay_proc(|guild_id| {
+        Duration::from_secs(configs.get(&guild_id).map_or(10, |c| c.decay_secs))
+    });
+
+    for (guild_id, burst) in finished {
+        let Some(raid_id) = burst.raid_id else {
+            continue;
+        };
+
+        if let Err(e) = end_raid(&data.database, raid_id).await {
+            println!("Failed to end raid #{raid_id}: {e}");
+        }
+
+        let Some(alert_channel) = configs.get(&guild_id).and_then(|c| c.alert_channel) else {
+            continue;
+        };
+
+        let builder = CreateMessage::new().content(format!(
+            "Raid #{raid_id} has ended after {} joins.",
+            burst.joins.len()
+        ));
+        log_to(ctx, alert_channel, builder).await;
+    }
+}
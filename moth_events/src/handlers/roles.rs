@@ -0,0 +1,143 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::{
+    helper::{colour_prefix, get_guild_name_override, get_permission_changes_detail},
+    Data, Error,
+};
+use moth_ansi::{HI_BLUE, RESET};
+use poise::serenity_prelude::{self as serenity, GuildId, Permissions, Role, RoleId};
+
+pub async fn guild_role_create(
+    ctx: &serenity::Context,
+    data: Arc<Data>,
+    role: &Role,
+) -> Result<(), Error> {
+    let guild_name = get_guild_name_override(ctx, &data, Some(role.guild_id));
+
+    let mut details = String::new();
+    if role.permissions != Permissions::empty() {
+        writeln!(details, "permissions:").unwrap();
+        details.push_str(&get_permission_changes_detail(
+            Permissions::empty(),
+            role.permissions,
+            true,
+        ));
+    }
+
+    println!(
+        "{HI_BLUE}[{guild_name}] Role {}{}{HI_BLUE} was created! (ID:{}){RESET}\n{}",
+        colour_prefix(role.colour),
+        role.name,
+        role.id,
+        details.trim_end()
+    );
+
+    Ok(())
+}
+
+pub async fn guild_role_update(
+    ctx: &serenity::Context,
+    data: Arc<Data>,
+    old: &Option<Role>,
+    new: &Role,
+) -> Result<(), Error> {
+    let Some(old) = old else {
+        return Ok(());
+    };
+
+    let guild_name = get_guild_name_override(ctx, &data, Some(new.guild_id));
+    let mut diff = String::new();
+
+    if old.name != new.name {
+        writeln!(diff, "Name: {} -> {}", old.name, new.name).unwrap();
+    }
+    if old.colour != new.colour {
+        writeln!(
+            diff,
+            "Colour: {}#{:06X}{RESET} -> {}#{:06X}{RESET}",
+            colour_prefix(old.colour),
+            old.colour.0,
+            colour_prefix(new.colour),
+            new.colour.0
+        )
+        .unwrap();
+    }
+    if old.hoist() != new.hoist() {
+        writeln!(diff, "Hoisted: {} -> {}", old.hoist(), new.hoist()).unwrap();
+    }
+    if old.mentionable() != new.mentionable() {
+        writeln!(
+            diff,
+            "Mentionable: {} -> {}",
+            old.mentionable(),
+            new.mentionable()
+        )
+        .unwrap();
+    }
+    if old.icon != new.icon {
+        writeln!(
+            diff,
+            "Icon: {} -> {}",
+            old.icon
+                .as_ref()
+                .map_or_else(|| "None".to_string(), ToString::to_string),
+            new.icon
+                .as_ref()
+                .map_or_else(|| "None".to_string(), ToString::to_string)
+        )
+        .unwrap();
+    }
+    if old.unicode_emoji != new.unicode_emoji {
+        writeln!(
+            diff,
+            "Emoji: {} -> {}",
+            old.unicode_emoji.as_deref().unwrap_or("None"),
+            new.unicode_emoji.as_deref().unwrap_or("None")
+        )
+        .unwrap();
+    }
+    if old.permissions != new.permissions {
+        writeln!(diff, "permissions:").unwrap();
+        diff.push_str(&get_permission_changes_detail(
+            old.permissions,
+            new.permissions,
+            true,
+        ));
+    }
+
+    // position changes are sent for every role below a moved role, so they are skipped.
+    let diff = diff.trim_end_matches('\n');
+    if !diff.is_empty() {
+        println!(
+            "{HI_BLUE}[{guild_name}] Role {}{}{HI_BLUE} was updated! (ID:{}){RESET}\n{diff}",
+            colour_prefix(new.colour),
+            new.name,
+            new.id
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn guild_role_delete(
+    ctx: &serenity::Context,
+    data: Arc<Data>,
+    guild_id: GuildId,
+    role_id: RoleId,
+    removed: &Option<Role>,
+) -> Result<(), Error> {
+    let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
+
+    if let Some(role) = removed {
+        println!(
+            "{HI_BLUE}[{guild_name}] Role {}{}{HI_BLUE} was deleted! (ID:{role_id}){RESET}",
+            colour_prefix(role.colour),
+            role.name
+        );
+    } else {
+        println!("{HI_BLUE}[{guild_name}] An uncached role was deleted! (ID:{role_id}){RESET}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use chrono::Utc;
+use poise::serenity_prelude::{GuildId, RoleId, UserId};
+use sqlx::query;
+
+use crate::Error;
+
+use moth_data::database::Database;
+
+pub(super) async fn insert_role_changes(
+    database: &Database,
+    guild_id: GuildId,
+    user_id: UserId,
+    added: &[RoleId],
+    removed: &[RoleId],
+) -> Result<(), Error> {
+    if added.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    database.insert_user(user_id).await?;
+    database.insert_guild(guild_id).await?;
+
+    let role_ids = added
+        .iter()
+        .chain(removed)
+        .map(|r| r.get() as i64)
+        .collect::<Vec<_>>();
+    let was_added = added
+        .iter()
+        .map(|_| true)
+        .chain(removed.iter().map(|_| false))
+        .collect::<Vec<_>>();
+
+    query!(
+        "INSERT INTO role_changes (guild_id, user_id, role_id, added, timestamp) SELECT $1, $2, \
+         role_id, added, $5 FROM UNNEST($3::BIGINT[], $4::BOOLEAN[]) AS c(role_id, added)",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        &role_ids,
+        &was_added,
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
@@ -3,13 +3,23 @@ use std::sync::Arc;
 use chrono::Utc;
 use moth_ansi::{HI_GREEN, RESET};
 use poise::serenity_prelude::{
-    self as serenity, ChannelId, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
-    GuildMemberUpdateEvent, Member,
+    self as serenity, ChannelId, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildId,
+    GuildMemberUpdateEvent, Member, RoleId,
 };
+use std::fmt::Write;
 
 use small_fixed_array::FixedString;
 
-use crate::{helper::get_guild_name_override, Data, Error};
+use crate::{
+    handlers::name_filter,
+    helper::{colour_prefix, get_guild_name_override},
+    Data, Error,
+};
+
+mod database;
+pub mod nickname;
+
+use database::insert_role_changes;
 
 pub async fn guild_member_update(
     ctx: &serenity::Context,
@@ -21,14 +31,25 @@ pub async fn guild_member_update(
     let guild_id = event.guild_id;
     let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
 
+    let previous_names =
+        name_filter::PreviousNames::new(&data, old_if_available.as_ref(), guild_id, event.user.id);
+
     // check names, maybe i should hook this into old info as well.
     data.check_or_insert_user(&event.user).await;
+    data.check_or_insert_avatars(&event.user).await;
+    data.check_or_insert_guild_avatar(guild_id, event.user.id, event.avatar.as_ref())
+        .await;
 
     if let Some(member) = new {
         let nick = member.nick.clone().map(|s| s.to_string());
 
         data.check_or_insert_nick(event.guild_id, event.user.id, nick)
             .await;
+
+        // a rename by the filter sends its own update, which the policy is checked against.
+        if !name_filter::check_member(ctx, &data, &previous_names, member).await? {
+            nickname::enforce_member(ctx, &data, old_if_available.as_ref(), member).await?;
+        }
     }
 
     if let Some(old_member) = old_if_available {
@@ -72,6 +93,38 @@ pub async fn guild_member_update(
                     new_member.user.id
                 );
             }
+
+            let added = new_member
+                .roles
+                .iter()
+                .filter(|r| !old_member.roles.contains(r))
+                .copied()
+                .collect::<Vec<_>>();
+            let removed = old_member
+                .roles
+                .iter()
+                .filter(|r| !new_member.roles.contains(r))
+                .copied()
+                .collect::<Vec<_>>();
+
+            if !added.is_empty() || !removed.is_empty() {
+                let changes = role_changes_string(ctx, guild_id, &added, &removed);
+                println!(
+                    "{HI_GREEN}[{}] Role change: {} (ID:{}){RESET}\n{changes}",
+                    guild_name,
+                    new_member.user.tag(),
+                    new_member.user.id
+                );
+
+                insert_role_changes(
+                    &data.database,
+                    guild_id,
+                    new_member.user.id,
+                    &added,
+                    &removed,
+                )
+                .await?;
+            }
         }
 
         if let Some(timestamp) = event.unusual_dm_activity_until {
@@ -134,6 +187,35 @@ pub async fn guild_member_update(
     Ok(())
 }
 
+fn role_changes_string(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    added: &[RoleId],
+    removed: &[RoleId],
+) -> String {
+    let mut changes = String::new();
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return changes;
+    };
+
+    for (prefix, roles) in [("+", added), ("-", removed)] {
+        for role_id in roles {
+            match guild.roles.get(role_id) {
+                Some(role) => writeln!(
+                    changes,
+                    "{prefix} {}{}{RESET}",
+                    colour_prefix(role.colour),
+                    role.name
+                ),
+                None => writeln!(changes, "{prefix} Unknown role ({role_id})"),
+            }
+            .unwrap();
+        }
+    }
+
+    changes.trim_end().to_string()
+}
+
 async fn dm_activity_new(
     ctx: &serenity::Context,
     event: &GuildMemberUpdateEvent,
@@ -0,0 +1,303 @@
+//! Keeps member names typeable and stops members hoisting themselves up the member list.
+
+use crate::helper::get_guild_name_override;
+use crate::moderation::{guild_config, log_to};
+use crate::{Data, Error};
+use moth_ansi::{HI_RED, RESET};
+use moth_config::NicknamePolicy;
+use poise::serenity_prelude::{
+    self as serenity, CreateAllowedMentions, CreateMessage, EditMember, GuildId, Member,
+};
+
+const REASON: &str = "Enforcing the nickname policy";
+
+/// Names need a run of this many typeable characters to be mentionable, unless they are typeable
+/// throughout.
+const MIN_TYPEABLE_RUN: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    Hoisted,
+    Confusable,
+    TooLong,
+    Unmentionable,
+}
+
+impl Violation {
+    #[must_use]
+    pub fn describe(self) -> &'static str {
+        match self {
+            Violation::Hoisted => "hoisted",
+            Violation::Confusable => "lookalike characters",
+            Violation::TooLong => "too long",
+            Violation::Unmentionable => "unmentionable",
+        }
+    }
+}
+
+/// The nickname a member should be given and why.
+pub struct Rename {
+    pub name: String,
+    pub violations: Vec<Violation>,
+}
+
+impl Rename {
+    #[must_use]
+    pub fn reasons(&self) -> String {
+        self.violations
+            .iter()
+            .map(|v| Violation::describe(*v))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The name a member is shown as, their nickname, global name or username.
+#[must_use]
+pub fn shown_name(member: &Member) -> &str {
+    member
+        .nick
+        .as_deref()
+        .or(member.user.global_name.as_deref())
+        .unwrap_or(member.user.name.as_str())
+}
+
+/// Removes punctuation, symbols and invisible characters from the start of a name.
+fn dehoist(name: &str) -> &str {
+    name.trim_start_matches(|c: char| !c.is_alphanumeric())
+}
+
+/// Letters and digits of any script count as typeable, keyboards exist for them.
+fn is_mentionable(name: &str) -> bool {
+    let mut run = 0;
+    let mut longest = 0;
+    let mut typeable = true;
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+            typeable &= c.is_ascii();
+        }
+    }
+
+    longest >= MIN_TYPEABLE_RUN || (longest > 0 && typeable)
+}
+
+/// The ascii a name mixing lookalikes from several scripts reads as.
+///
+/// Names written in a single script are left alone even if every letter looks like ascii, as
+/// that is just how they are spelled. Every letter also has to have an ascii lookalike.
+fn unconfuse(name: &str) -> Option<String> {
+    if !moth_filter::is_mixed_script(name) {
+        return None;
+    }
+
+    let mut ascii = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii() {
+            ascii.push(c);
+        } else if c.is_alphanumeric() {
+            // only the lookalikes are mapped, as skeletons turn ascii like `m` into `rn`.
+            let lookalike = moth_filter::skeleton(c.encode_utf8(&mut [0; 4]));
+            if lookalike.is_empty() || !lookalike.chars().all(|l| l.is_ascii_alphanumeric()) {
+                return None;
+            }
+            ascii.push_str(&lookalike);
+        } else if c.is_whitespace() {
+            ascii.push(' ');
+        }
+    }
+
+    Some(ascii.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn truncate(name: &str, max_length: Option<usize>) -> Option<String> {
+    let max_length = max_length?;
+    if name.chars().count() <= max_length {
+        return None;
+    }
+
+    Some(
+        name.chars()
+            .take(max_length)
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    )
+}
+
+/// The username, or the configured fallback if even that can't be used.
+fn fallback(policy: &NicknamePolicy, username: &str) -> String {
+    let username = if policy.dehoist {
+        dehoist(username)
+    } else {
+        username
+    };
+
+    let name = if is_mentionable(username) {
+        username
+    } else {
+        policy.fallback_name.as_str()
+    };
+
+    truncate(name, policy.max_length).unwrap_or_else(|| name.to_string())
+}
+
+/// Checks a name against the policy, returning what it should be changed to.
+#[must_use]
+pub fn enforce(policy: &NicknamePolicy, name: &str, username: &str) -> Option<Rename> {
+    let mut violations = Vec::new();
+    let mut new = name.to_string();
+
+    if policy.dehoist {
+        let dehoisted = dehoist(&new);
+        if dehoisted.len() != new.len() {
+            violations.push(Violation::Hoisted);
+            new = dehoisted.to_string();
+        }
+    }
+
+    if policy.replace_confusable {
+        if let Some(ascii) = unconfuse(&new) {
+            violations.push(Violation::Confusable);
+            new = ascii;
+        }
+    }
+
+    if let Some(truncated) = truncate(&new, policy.max_length) {
+        violations.push(Violation::TooLong);
+        new = truncated;
+    }
+
+    // checked last, as truncating can cut off the only typeable part.
+    if policy.replace_unmentionable && !is_mentionable(&new) {
+        violations.push(Violation::Unmentionable);
+        new = fallback(policy, username);
+    } else if new.is_empty() {
+        new = fallback(policy, username);
+    }
+
+    (new != name).then_some(Rename {
+        name: new,
+        violations,
+    })
+}
+
+/// Renames a member that breaks the guilds nickname policy, when they join or change their name.
+pub async fn enforce_member(
+    ctx: &serenity::Context,
+    data: &Data,
+    old: Option<&Member>,
+    member: &Member,
+) -> Result<(), Error> {
+    let guild_id = member.guild_id;
+
+    if member.user.bot() || old.is_some_and(|o| shown_name(o) == shown_name(member)) {
+        return Ok(());
+    }
+
+    let Some(policy) = guild_config(data, guild_id, |e| e.nickname_policy.as_ref()) else {
+        return Ok(());
+    };
+
+    if member.roles.iter().any(|r| policy.exempt_roles.contains(r)) {
+        return Ok(());
+    }
+
+    let name = shown_name(member);
+    let Some(rename) = enforce(&policy, name, &member.user.name) else {
+        return Ok(());
+    };
+
+    let builder = EditMember::new()
+        .nickname(rename.name.as_str())
+        .audit_log_reason(REASON);
+    let result = match guild_id
+        .edit_member(&ctx.http, member.user.id, builder)
+        .await
+    {
+        Ok(_) => "Renamed",
+        Err(e) => {
+            println!("Failed to rename {}: {e}", member.user.id);
+            "Failed to rename"
+        }
+    };
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+    println!(
+        "{HI_RED}[{guild_name}] {result} {}: {name} -> {} ({}){RESET}",
+        member.user.tag(),
+        rename.name,
+        rename.reasons()
+    );
+
+    let Some(log_channel) = policy.log_channel else {
+        return Ok(());
+    };
+
+    let content = format!(
+        "{result} <@{}> for breaking the nickname policy ({}):\n`{name}` -> `{}`",
+        member.user.id,
+        rename.reasons(),
+        rename.name
+    );
+    let builder = CreateMessage::new().content(content).allowed_mentions(
+        CreateAllowedMentions::new()
+            .all_users(false)
+            .all_roles(false)
+            .everyone(false),
+    );
+    log_to(ctx, log_channel, builder).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> NicknamePolicy {
+        NicknamePolicy::default()
+    }
+
+    #[test]
+    fn non_latin_names_are_left_alone() {
+        for name in ["Дмитрий", "Вера", "山田太郎", "محمد", "Ελένη", "김민준"]
+        {
+            assert!(
+                enforce(&policy(), name, "user").is_none(),
+                "{name} was renamed"
+            );
+        }
+    }
+
+    #[test]
+    fn mixed_script_lookalikes_are_replaced() {
+        let rename = enforce(&policy(), "m\u{43e}th", "user").unwrap();
+        assert_eq!(rename.name, "moth");
+        assert_eq!(rename.violations, [Violation::Confusable]);
+    }
+
+    #[test]
+    fn symbols_are_unmentionable() {
+        let rename = enforce(&policy(), "x★★", "user").unwrap();
+        assert_eq!(rename.name, "user");
+        assert_eq!(rename.violations, [Violation::Unmentionable]);
+    }
+
+    #[test]
+    fn hoisted_names_are_dehoisted() {
+        let rename = enforce(&policy(), "!!moth", "user").unwrap();
+        assert_eq!(rename.name, "moth");
+        assert_eq!(rename.violations, [Violation::Hoisted]);
+    }
+
+    #[test]
+    fn single_letters_are_mentionable() {
+        assert!(is_mentionable("a"));
+        assert!(is_mentionable("я"));
+        assert!(!is_mentionable("a★"));
+    }
+}
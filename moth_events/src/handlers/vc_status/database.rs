@@ -0,0 +1,54 @@
+use chrono::Utc;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::query;
+
+use crate::Error;
+
+use moth_data::database::Database;
+
+/// Records a status change before it is attributed, returning the id of the new row.
+pub(super) async fn insert_status_change(
+    database: &Database,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    old: Option<&str>,
+    new: Option<&str>,
+) -> Result<i32, Error> {
+    database.insert_guild(guild_id).await?;
+
+    let record = query!(
+        "INSERT INTO vc_status_history (guild_id, channel_id, old_status, new_status, timestamp) \
+         VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        guild_id.get() as i64,
+        channel_id.get() as i64,
+        old,
+        new,
+        Utc::now().naive_utc()
+    )
+    .fetch_one(&database.db)
+    .await?;
+
+    Ok(record.id)
+}
+
+pub(super) async fn attribute_status_change(
+    database: &Database,
+    history_id: i32,
+    user_id: UserId,
+    blacklisted: bool,
+    cleared: bool,
+) -> Result<(), Error> {
+    database.insert_user(user_id).await?;
+
+    query!(
+        "UPDATE vc_status_history SET user_id = $2, blacklisted = $3, cleared = $4 WHERE id = $1",
+        history_id,
+        user_id.get() as i64,
+        blacklisted,
+        cleared
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
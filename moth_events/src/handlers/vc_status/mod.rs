@@ -0,0 +1,231 @@
+mod database;
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::{helper::get_user, Data, Error};
+use database::{attribute_status_change, insert_status_change};
+use moth_data::structs::PendingVcStatus;
+use poise::serenity_prelude::audit_log::Action::VoiceChannelStatus;
+use poise::serenity_prelude::{
+    self as serenity, AuditLogEntry, ChannelId, CreateEmbed, GuildId, UserId,
+    VoiceChannelStatusAction,
+};
+
+pub async fn voice_channel_status_update(
+    ctx: &serenity::Context,
+    old: &Option<String>,
+    status: &Option<String>,
+    id: &ChannelId,
+    guild_id: &GuildId,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    if !data.config.read().vcstatus.action {
+        return Ok(());
+    }
+
+    let old = old.as_deref().filter(|s| !s.is_empty());
+    let new = status.as_deref().filter(|s| !s.is_empty());
+    if old == new {
+        return Ok(());
+    }
+
+    let history_id = insert_status_change(&data.database, *guild_id, *id, old, new).await?;
+    let pending = PendingVcStatus {
+        history_id,
+        guild_id: *guild_id,
+        old: old.map(ToString::to_string),
+        received: Instant::now(),
+    };
+
+    let key = (*id, new.unwrap_or_default().to_string());
+    if let Some((pending, user_id)) = data.vc_status_correlation.status_received(key, pending) {
+        attribute(ctx, &data, *id, new, pending, user_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Matches status updates and removals in the audit log against received status updates.
+pub async fn vc_status_audit(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    entry: &AuditLogEntry,
+) -> Result<(), Error> {
+    let is_delete = match entry.action {
+        VoiceChannelStatus(VoiceChannelStatusAction::StatusUpdate) => false,
+        VoiceChannelStatus(VoiceChannelStatusAction::StatusDelete) => true,
+        _ => return Ok(()),
+    };
+
+    let (Some(user_id), Some(options)) = (entry.user_id, &entry.options) else {
+        return Ok(());
+    };
+    let Some(channel_id) = options.channel_id else {
+        return Ok(());
+    };
+
+    let status = if is_delete {
+        String::new()
+    } else {
+        options.status.as_deref().unwrap_or_default().to_string()
+    };
+
+    let new = (!status.is_empty()).then(|| status.clone());
+    if let Some((pending, user_id)) = data
+        .vc_status_correlation
+        .author_received((channel_id, status), user_id)
+    {
+        attribute(ctx, data, channel_id, new.as_deref(), pending, user_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Handles a status change once both its update and audit log entry have arrived.
+async fn attribute(
+    ctx: &serenity::Context,
+    data: &Data,
+    channel_id: ChannelId,
+    new: Option<&str>,
+    pending: PendingVcStatus,
+    user_id: UserId,
+) -> Result<(), Error> {
+    let vcstatus = {
+        let config = data.config.read();
+        config.vcstatus.clone()
+    };
+
+    // check if regex for blacklists exist and if a new status exists.
+    let blacklisted = if let (Some(regex_patterns), Some(value)) = (&vcstatus.regex, new) {
+        check_blacklisted(value, regex_patterns)
+    } else {
+        false
+    };
+
+    let mut cleared = false;
+    if blacklisted && vcstatus.blacklist_detection && vcstatus.clear_blacklisted {
+        let map = serde_json::json!({ "status": "" });
+        match ctx
+            .http
+            .edit_voice_status(channel_id, &map, Some("Blacklisted voice channel status"))
+            .await
+        {
+            Ok(()) => cleared = true,
+            Err(e) => println!("Failed to clear the status of {channel_id}: {e}"),
+        }
+    }
+
+    attribute_status_change(
+        &data.database,
+        pending.history_id,
+        user_id,
+        blacklisted,
+        cleared,
+    )
+    .await?;
+
+    post_messages(
+        ctx,
+        data,
+        channel_id,
+        pending.guild_id,
+        pending.old.as_deref(),
+        new,
+        user_id,
+        blacklisted,
+        cleared,
+    )
+    .await?;
+    Ok(())
+}
+
+fn check_blacklisted(msg: &str, patterns: &[regex::Regex]) -> bool {
+    patterns.iter().any(|pattern| pattern.is_match(msg))
+}
+
+// should probably fix the ordering of arguments.
+#[allow(clippy::too_many_arguments)]
+async fn post_messages(
+    ctx: &serenity::Context,
+    data: &Data,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    old: Option<&str>,
+    new: Option<&str>,
+    user_id: UserId,
+    blacklisted: bool,
+    cleared: bool,
+) -> Result<(), Error> {
+    let channel_str: &str = &format!("<#{}>", channel_id.get());
+
+    let old_field = match old {
+        Some(value) if !value.is_empty() => ("Old", value, true),
+        _ => ("Old", "None", true),
+    };
+
+    let new_field = match new {
+        Some(value) if !value.is_empty() => ("New", value, true),
+        _ => ("New", "None", true),
+    };
+
+    let fields = [("Channel", channel_str, true), old_field, new_field];
+
+    let Some(user) = get_user(ctx, guild_id, user_id).await else {
+        return Ok(());
+    };
+    let author_title = format!("{} changed a channel status", user.name);
+    let author = serenity::CreateEmbedAuthor::new(author_title).icon_url(user.face());
+    let footer = serenity::CreateEmbedFooter::new(format!("User ID: {}", user.id.get()));
+
+    let embed = serenity::CreateEmbed::default()
+        .fields(fields)
+        .author(author)
+        .footer(footer);
+
+    send_msgs(ctx, data, user_id, embed, blacklisted, cleared).await?;
+
+    Ok(())
+}
+
+async fn send_msgs(
+    ctx: &serenity::Context,
+    data: &Data,
+    user_id: UserId,
+    embed: CreateEmbed<'_>,
+    blacklisted: bool,
+    cleared: bool,
+) -> Result<(), Error> {
+    let (post, announce) = {
+        let status = &data.config.read().vcstatus;
+        (status.post_channel, status.announce_channel)
+    };
+
+    let content = match (blacklisted, cleared) {
+        (true, true) => format!("<@{user_id}>: **Blacklisted word in status!** (cleared)"),
+        (true, false) => format!("<@{user_id}>: **Blacklisted word in status!**"),
+        _ => format!("<@{user_id}>"),
+    };
+
+    let mentions = serenity::CreateAllowedMentions::new()
+        .all_users(false)
+        .everyone(false)
+        .all_roles(false);
+
+    let msg = serenity::CreateMessage::default()
+        .content(&content)
+        .embed(embed)
+        .allowed_mentions(mentions);
+
+    if blacklisted {
+        if let Some(announce) = announce {
+            announce.send_message(&ctx.http, msg.clone()).await?;
+        }
+    }
+
+    if let Some(post) = post {
+        post.send_message(&ctx.http, msg).await?;
+    }
+
+    Ok(())
+}
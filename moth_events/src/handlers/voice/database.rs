@@ -0,0 +1,225 @@
+use chrono::Utc;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::query;
+
+use crate::Error;
+
+use moth_data::database::{Database, VoiceActivityKind};
+
+/// Opens a new voice session, closing any session the member still has open in the guild.
+pub(super) async fn open_session(
+    database: &Database,
+    guild_id: GuildId,
+    user_id: UserId,
+    channel_id: ChannelId,
+    switched_from: Option<ChannelId>,
+) -> Result<(), Error> {
+    database.insert_user(user_id).await?;
+    database.insert_guild(guild_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let mut transaction = database.db.begin().await?;
+
+    query!(
+        "UPDATE voice_sessions SET left_at = $3 WHERE guild_id = $1 AND user_id = $2 AND left_at \
+         IS NULL",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        now
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    query!(
+        "INSERT INTO voice_sessions (guild_id, user_id, channel_id, switched_from, joined_at) \
+         VALUES ($1, $2, $3, $4, $5)",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        channel_id.get() as i64,
+        switched_from.map(|c| c.get() as i64),
+        now
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub(super) async fn close_session(
+    database: &Database,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<(), Error> {
+    query!(
+        "UPDATE voice_sessions SET left_at = $3 WHERE guild_id = $1 AND user_id = $2 AND left_at \
+         IS NULL",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks the open sessions and activities of the given members as still ongoing.
+pub(super) async fn touch_sessions(
+    database: &Database,
+    members: &[(GuildId, UserId)],
+) -> Result<(), Error> {
+    let guild_ids = members
+        .iter()
+        .map(|(g, _)| g.get() as i64)
+        .collect::<Vec<_>>();
+    let user_ids = members
+        .iter()
+        .map(|(_, u)| u.get() as i64)
+        .collect::<Vec<_>>();
+
+    let now = Utc::now().naive_utc();
+    let mut transaction = database.db.begin().await?;
+
+    query!(
+        "UPDATE voice_sessions v SET last_seen = $3 FROM UNNEST($1::BIGINT[], $2::BIGINT[]) AS \
+         m(guild_id, user_id) WHERE v.guild_id = m.guild_id AND v.user_id = m.user_id AND \
+         v.left_at IS NULL",
+        &guild_ids,
+        &user_ids,
+        now
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    query!(
+        "UPDATE voice_activity v SET last_seen = $3 FROM UNNEST($1::BIGINT[], $2::BIGINT[]) AS \
+         m(guild_id, user_id) WHERE v.guild_id = m.guild_id AND v.user_id = m.user_id AND \
+         v.ended_at IS NULL",
+        &guild_ids,
+        &user_ids,
+        now
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Closes the sessions a guild still has open at when they were last seen, then opens sessions
+/// for members already in voice.
+///
+/// Returns how many sessions were closed and how many were opened.
+pub(super) async fn resume_sessions(
+    database: &Database,
+    guild_id: GuildId,
+    states: &[(UserId, ChannelId)],
+) -> Result<(u64, u64), Error> {
+    let user_ids = states
+        .iter()
+        .map(|(u, _)| u.get() as i64)
+        .collect::<Vec<_>>();
+    let channel_ids = states
+        .iter()
+        .map(|(_, c)| c.get() as i64)
+        .collect::<Vec<_>>();
+
+    database.insert_guild(guild_id).await?;
+
+    let mut transaction = database.db.begin().await?;
+
+    // sessions that were never seen after opening end where they started, not counting downtime.
+    let closed = query!(
+        "UPDATE voice_sessions SET left_at = COALESCE(last_seen, joined_at) WHERE guild_id = $1 \
+         AND left_at IS NULL",
+        guild_id.get() as i64
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    query!(
+        "UPDATE voice_activity SET ended_at = COALESCE(last_seen, started_at) WHERE guild_id = $1 \
+         AND ended_at IS NULL",
+        guild_id.get() as i64
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    if states.is_empty() {
+        transaction.commit().await?;
+        return Ok((closed.rows_affected(), 0));
+    }
+
+    query!(
+        "INSERT INTO users (user_id) SELECT * FROM UNNEST($1::BIGINT[]) ON CONFLICT (user_id) DO \
+         NOTHING",
+        &user_ids
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let opened = query!(
+        "INSERT INTO voice_sessions (guild_id, user_id, channel_id, joined_at) SELECT $1, \
+         user_id, channel_id, $4 FROM UNNEST($2::BIGINT[], $3::BIGINT[]) AS s(user_id, \
+         channel_id) ON CONFLICT (guild_id, user_id) WHERE left_at IS NULL DO NOTHING",
+        guild_id.get() as i64,
+        &user_ids,
+        &channel_ids,
+        Utc::now().naive_utc()
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok((closed.rows_affected(), opened.rows_affected()))
+}
+
+pub(super) async fn start_activity(
+    database: &Database,
+    guild_id: GuildId,
+    user_id: UserId,
+    channel_id: ChannelId,
+    kind: VoiceActivityKind,
+) -> Result<(), Error> {
+    database.insert_user(user_id).await?;
+    database.insert_guild(guild_id).await?;
+
+    query!(
+        "INSERT INTO voice_activity (guild_id, user_id, channel_id, kind, started_at) VALUES ($1, \
+         $2, $3, $4, $5) ON CONFLICT (guild_id, user_id, kind) WHERE ended_at IS NULL DO NOTHING",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        channel_id.get() as i64,
+        kind as VoiceActivityKind,
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends an open activity of the given kind, or every open activity if no kind is given.
+pub(super) async fn end_activity(
+    database: &Database,
+    guild_id: GuildId,
+    user_id: UserId,
+    kind: Option<VoiceActivityKind>,
+) -> Result<(), Error> {
+    query!(
+        "UPDATE voice_activity SET ended_at = $4 WHERE guild_id = $1 AND user_id = $2 AND \
+         ($3::voiceactivitykind IS NULL OR kind = $3) AND ended_at IS NULL",
+        guild_id.get() as i64,
+        user_id.get() as i64,
+        kind as Option<VoiceActivityKind>,
+        Utc::now().naive_utc()
+    )
+    .execute(&database.db)
+    .await?;
+
+    Ok(())
+}
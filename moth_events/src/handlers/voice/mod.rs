@@ -0,0 +1,416 @@
+mod database;
+
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::{
+    helper::{get_guild_name_override, get_user},
+    Data, Error,
+};
+use database::{
+    close_session, end_activity, open_session, resume_sessions, start_activity, touch_sessions,
+};
+use moth_ansi::{GREEN, RESET};
+use moth_data::{database::VoiceActivityKind, structs::VoiceModeration};
+use poise::serenity_prelude::{
+    self as serenity, AuditLogEntry, Change, ChannelId, Guild, GuildId, MemberAction, UserId,
+    VoiceState,
+};
+use serenity::model::guild::audit_log::Action;
+
+pub async fn voice_state_update(
+    ctx: &serenity::Context,
+    old: &Option<VoiceState>,
+    new: &VoiceState,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    if let Some(old) = old {
+        if old.channel_id != new.channel_id && new.channel_id.is_some() {
+            if let Some(guild_id) = new.guild_id {
+                open_session(
+                    &data.database,
+                    guild_id,
+                    new.user_id,
+                    new.channel_id.unwrap(),
+                    old.channel_id,
+                )
+                .await?;
+            }
+            handle_switch(ctx, old, new).await?;
+        } else if new.channel_id.is_none() {
+            if let Some(guild_id) = new.guild_id {
+                close_session(&data.database, guild_id, new.user_id).await?;
+                end_activity(&data.database, guild_id, new.user_id, None).await?;
+            }
+            handle_leave(ctx, old, new).await?;
+        }
+
+        if old.channel_id.is_some() && new.channel_id.is_some() {
+            handle_state_changes(ctx, &data, old, new).await?;
+        }
+    } else if let Some(channel_id) = new.channel_id {
+        if let Some(guild_id) = new.guild_id {
+            open_session(&data.database, guild_id, new.user_id, channel_id, None).await?;
+            sync_activities(&data, None, new).await?;
+        }
+        handle_joins(ctx, new).await?;
+    } else if let Some(guild_id) = new.guild_id {
+        // a leave without the old state cached, there's nothing to log but the session still ends.
+        close_session(&data.database, guild_id, new.user_id).await?;
+        end_activity(&data.database, guild_id, new.user_id, None).await?;
+    }
+
+    Ok(())
+}
+
+/// Records that the sessions of everyone the cache still shows in voice are ongoing.
+///
+/// Sessions left open by a restart are closed at the last time they were seen.
+pub async fn touch_voice_sessions(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let members = ctx
+        .cache
+        .guilds()
+        .into_iter()
+        .filter_map(|guild_id| ctx.cache.guild(guild_id))
+        .flat_map(|guild| {
+            guild
+                .voice_states
+                .iter()
+                .filter(|v| v.channel_id.is_some())
+                .map(|v| (guild.id, v.user_id))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    touch_sessions(&data.database, &members).await
+}
+
+/// Closes the sessions left open since a guild was last received and opens sessions for
+/// everyone currently in voice.
+pub async fn resume_voice_sessions(data: &Data, guild: &Guild) -> Result<(), Error> {
+    let states = guild
+        .voice_states
+        .iter()
+        .filter_map(|v| v.channel_id.map(|c| (v.user_id, c)))
+        .collect::<Vec<_>>();
+
+    let (closed, _) = resume_sessions(&data.database, guild.id, &states).await?;
+    if closed != 0 {
+        println!(
+            "{GREEN}[{}] Closed {closed} voice sessions left open since the last session{RESET}",
+            guild.name
+        );
+    }
+
+    for state in guild.voice_states.iter().filter(|v| v.channel_id.is_some()) {
+        sync_activities(data, None, state).await?;
+    }
+
+    Ok(())
+}
+
+fn is_streaming(state: &VoiceState) -> bool {
+    state.self_stream().unwrap_or(false)
+}
+
+/// Starts or ends stream and camera time when they change between two voice states.
+async fn sync_activities(
+    data: &Data,
+    old: Option<&VoiceState>,
+    new: &VoiceState,
+) -> Result<(), Error> {
+    let (Some(guild_id), Some(channel_id)) = (new.guild_id, new.channel_id) else {
+        return Ok(());
+    };
+
+    let kinds = [
+        (
+            VoiceActivityKind::Stream,
+            old.is_some_and(is_streaming),
+            is_streaming(new),
+        ),
+        (
+            VoiceActivityKind::Video,
+            old.is_some_and(VoiceState::self_video),
+            new.self_video(),
+        ),
+    ];
+
+    for (kind, was_active, is_active) in kinds {
+        match (was_active, is_active) {
+            (false, true) => {
+                start_activity(&data.database, guild_id, new.user_id, channel_id, kind).await?;
+            }
+            (true, false) => {
+                end_activity(&data.database, guild_id, new.user_id, Some(kind)).await?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_state_changes(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    old: &VoiceState,
+    new: &VoiceState,
+) -> Result<(), Error> {
+    let (Some(guild_id), Some(channel_id)) = (new.guild_id, new.channel_id) else {
+        return Ok(());
+    };
+
+    sync_activities(data, Some(old), new).await?;
+
+    let flags = [
+        ("Self muted", old.self_mute(), new.self_mute()),
+        ("Self deafened", old.self_deaf(), new.self_deaf()),
+        ("Server muted", old.mute(), new.mute()),
+        ("Server deafened", old.deaf(), new.deaf()),
+        ("Streaming", is_streaming(old), is_streaming(new)),
+        ("Camera", old.self_video(), new.self_video()),
+        ("Suppressed", old.suppress(), new.suppress()),
+        (
+            "Requesting to speak",
+            old.request_to_speak_timestamp.is_some(),
+            new.request_to_speak_timestamp.is_some(),
+        ),
+    ];
+
+    let mut diff = String::new();
+    for (name, old, new) in flags {
+        if old != new {
+            writeln!(diff, "{name}: {old} -> {new}").unwrap();
+        }
+    }
+
+    let moderations = [
+        (old.mute() != new.mute()).then_some(VoiceModeration::Mute(new.mute())),
+        (old.deaf() != new.deaf()).then_some(VoiceModeration::Deaf(new.deaf())),
+    ];
+    for moderation in moderations.into_iter().flatten() {
+        if let Some(moderator_id) = data
+            .voice_moderation_correlation
+            .state_received((guild_id, new.user_id, moderation), channel_id)
+        {
+            log_voice_moderation(
+                ctx,
+                data,
+                guild_id,
+                channel_id,
+                new.user_id,
+                moderator_id,
+                moderation,
+            )
+            .await;
+        }
+    }
+
+    let diff = diff.trim_end_matches('\n');
+    if diff.is_empty() {
+        return Ok(());
+    }
+
+    let user_name = match get_user(ctx, guild_id, new.user_id).await {
+        Some(user) => user.tag(),
+        None => return Ok(()),
+    };
+
+    let channel_name = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.channels.get(&channel_id).map(|c| c.name.to_string()))
+        .unwrap_or_else(|| String::from("None"));
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+
+    println!(
+        "{GREEN}[{guild_name}] {user_name} changed their voice state in {channel_name} \
+         (ID:{channel_id}){RESET}\n{diff}"
+    );
+
+    Ok(())
+}
+
+/// Attributes server mutes and deafens to the moderator responsible through the audit log.
+pub async fn voice_moderation_audit(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    entry: &AuditLogEntry,
+    guild_id: GuildId,
+) -> Result<(), Error> {
+    if !matches!(entry.action, Action::Member(MemberAction::Update)) {
+        return Ok(());
+    }
+
+    let (Some(moderator_id), Some(target_id)) = (entry.user_id, entry.target_id) else {
+        return Ok(());
+    };
+    let target_id = UserId::new(target_id.get());
+
+    for change in entry.changes.iter().flatten() {
+        let moderation = match change {
+            Change::Mute { new, .. } => VoiceModeration::Mute(new.unwrap_or(false)),
+            Change::Deaf { new, .. } => VoiceModeration::Deaf(new.unwrap_or(false)),
+            _ => continue,
+        };
+
+        if let Some(channel_id) = data
+            .voice_moderation_correlation
+            .moderator_received((guild_id, target_id, moderation), moderator_id)
+        {
+            log_voice_moderation(
+                ctx,
+                data,
+                guild_id,
+                channel_id,
+                target_id,
+                moderator_id,
+                moderation,
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Logs a server mute or deafen once both its voice state update and audit log entry arrived.
+async fn log_voice_moderation(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    target_id: UserId,
+    moderator_id: UserId,
+    moderation: VoiceModeration,
+) {
+    let moderator = get_user(ctx, guild_id, moderator_id)
+        .await
+        .map_or_else(|| moderator_id.to_string(), |u| u.tag());
+    let target = get_user(ctx, guild_id, target_id)
+        .await
+        .map_or_else(|| target_id.to_string(), |u| u.tag());
+
+    let channel_name = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.channels.get(&channel_id).map(|c| c.name.to_string()))
+        .unwrap_or_else(|| String::from("None"));
+
+    let guild_name = get_guild_name_override(ctx, data, Some(guild_id));
+
+    println!(
+        "{GREEN}[{guild_name}] {moderator} (ID:{moderator_id}) {} {target} (ID:{target_id}) in \
+         {channel_name} (ID:{channel_id}){RESET}",
+        moderation.describe()
+    );
+}
+
+async fn handle_switch(
+    ctx: &serenity::Context,
+    old: &VoiceState,
+    new: &VoiceState,
+) -> Result<(), Error> {
+    // unwrapping this is probably fine considering i already handle this before?
+    // I don't think i've seen a panic here?
+    let old_id = old.channel_id.unwrap();
+
+    // Ditto.
+    let new_id = new.channel_id.unwrap();
+
+    let user_name = match get_user(ctx, new.guild_id.unwrap(), new.user_id).await {
+        Some(user) => user.tag(),
+        None => return Ok(()),
+    };
+
+    let guild_cache = ctx.cache.guild(new.guild_id.unwrap());
+    // will fire real error in the future.
+
+    let Some(guild_cache) = guild_cache else {
+        return Ok(());
+    };
+
+    let channel_old_name = guild_cache.channels.get(&old_id).map(|c| &c.name);
+    let channel_new_name = guild_cache.channels.get(&new_id).map(|c| &c.name);
+
+    // maybe i should use fixedstring directly?
+    let old_name: Cow<str> = if let Some(channel_name) = channel_old_name {
+        Cow::Borrowed(channel_name)
+    } else {
+        Cow::Borrowed("None")
+    };
+
+    // ditto
+    let new_name: Cow<str> = if let Some(channel_name) = channel_new_name {
+        Cow::Borrowed(channel_name)
+    } else {
+        Cow::Borrowed("None")
+    };
+
+    let guild_name = get_guild_name_override(ctx, &ctx.data(), new.guild_id);
+
+    println!(
+        "{GREEN}[{guild_name}] {user_name}: {old_name} (ID:{old_id}) -> {new_name} \
+         (ID:{new_id}){RESET}"
+    );
+
+    Ok(())
+}
+async fn handle_leave(
+    ctx: &serenity::Context,
+    old: &VoiceState,
+    new: &VoiceState,
+) -> Result<(), Error> {
+    // There is no new channel ID.
+    let channel_id = old.channel_id.unwrap();
+    // they are leaving so old should hold the guild_id, see handle_joins for justification.
+    let user_name = match get_user(ctx, new.guild_id.unwrap(), new.user_id).await {
+        Some(user) => user.tag(),
+        None => return Ok(()),
+    };
+
+    let guild_cache = ctx.cache.guild(new.guild_id.unwrap());
+    // will fire real error in the future.
+    let Some(guild_cache) = guild_cache else {
+        return Ok(());
+    };
+
+    let channel_name = guild_cache
+        .channels
+        .get(&channel_id)
+        .map_or_else(|| "None", |c| c.name.as_str());
+
+    let guild_name = get_guild_name_override(ctx, &ctx.data(), new.guild_id);
+
+    println!("{GREEN}[{guild_name}] {user_name} left {channel_name} (ID:{channel_id}){RESET}");
+    Ok(())
+}
+async fn handle_joins(ctx: &serenity::Context, new: &VoiceState) -> Result<(), Error> {
+    let channel_id = new.channel_id.unwrap();
+
+    // unwrapping the guild should be fine here unless the discord api is being funky
+    // they are joining, so a guild_id is present.
+    let user_name = match get_user(ctx, new.guild_id.unwrap(), new.user_id).await {
+        Some(user) => user.tag(),
+        None => return Ok(()),
+    };
+
+    let guild_cache = ctx.cache.guild(new.guild_id.unwrap());
+    // will fire real error in the future.
+
+    let Some(guild_cache) = guild_cache else {
+        return Ok(());
+    };
+
+    let channel = guild_cache.channels.get(&channel_id).unwrap();
+
+    let channel_name = &channel.name;
+
+    let guild_name = get_guild_name_override(ctx, &ctx.data(), Some(channel.guild_id));
+
+    println!("{GREEN}[{guild_name}] {user_name} joined {channel_name} (ID:{channel_id}){RESET}");
+    Ok(())
+}
@@ -5,8 +5,9 @@ use std::sync::Arc;
 
 use moth_ansi::{HI_GREEN, RED, RESET};
 use poise::serenity_prelude::{
-    self as serenity, AutoArchiveDuration, ChannelId, ChannelType, Context, ForumLayoutType,
-    GuildId, PermissionOverwrite, PermissionOverwriteType, Permissions, SortOrder, User, UserId,
+    self as serenity, AutoArchiveDuration, ChannelId, ChannelType, Colour, Context,
+    ForumLayoutType, GuildId, PermissionOverwrite, PermissionOverwriteType, Permissions, SortOrder,
+    User, UserId,
 };
 
 // this function serves to help reduce the magic usage of to_user, serenity no longer
@@ -113,6 +114,16 @@ fn retrieve_cached_name(
     }
 }
 
+/// The ANSI escape for a role colour, empty for roles without a colour.
+#[must_use]
+pub fn colour_prefix(colour: Colour) -> String {
+    if colour.0 == 0 {
+        String::new()
+    } else {
+        format!("\x1B[38;2;{};{};{}m", colour.r(), colour.g(), colour.b())
+    }
+}
+
 #[must_use]
 pub fn channel_type_to_string(channel_type: ChannelType) -> String {
     match channel_type {
@@ -17,6 +17,7 @@ use moth_data::structs::{Data, Error};
 use poise::serenity_prelude::{self as serenity, FullEvent};
 
 pub mod helper;
+pub mod moderation;
 
 pub mod handlers;
 use handlers::*;
@@ -53,11 +54,35 @@ pub async fn event_handler(
             reactions::reaction_remove(ctx, removed_reaction, data).await?;
         }
         FullEvent::GuildCreate { guild, is_new } => {
-            guilds::guild_create(ctx, guild, is_new).await?;
+            guilds::guild_create(ctx, guild, is_new, data).await?;
+        }
+        FullEvent::GuildUpdate {
+            old_data_if_available,
+            new_data,
+        } => {
+            guilds::guild_update(ctx, data, old_data_if_available, new_data).await?;
+        }
+        FullEvent::GuildEmojisUpdate {
+            guild_id,
+            current_state,
+        } => {
+            expressions::guild_emojis_update(ctx, data, *guild_id, current_state).await?;
+        }
+        FullEvent::GuildStickersUpdate {
+            guild_id,
+            current_state,
+        } => {
+            expressions::guild_stickers_update(ctx, data, *guild_id, current_state).await?;
         }
         FullEvent::GuildMemberAddition { new_member } => {
             guilds::guild_member_addition(ctx, data, new_member).await?;
         }
+        FullEvent::InviteCreate { data: event } => {
+            invites::invite_create(&data, event).await?;
+        }
+        FullEvent::InviteDelete { data: event } => {
+            invites::invite_delete(&data, event).await?;
+        }
         FullEvent::GuildMemberRemoval {
             guild_id,
             user,
@@ -65,7 +90,43 @@ pub async fn event_handler(
         } => {
             guilds::guild_member_removal(ctx, guild_id, user, data).await?;
         }
+        FullEvent::GuildRoleCreate { new } => {
+            roles::guild_role_create(ctx, data, new).await?;
+        }
+        FullEvent::GuildRoleUpdate {
+            old_data_if_available,
+            new,
+        } => {
+            roles::guild_role_update(ctx, data, old_data_if_available, new).await?;
+        }
+        FullEvent::GuildRoleDelete {
+            guild_id,
+            removed_role_id,
+            removed_role_data_if_available,
+        } => {
+            roles::guild_role_delete(
+                ctx,
+                data,
+                *guild_id,
+                *removed_role_id,
+                removed_role_data_if_available,
+            )
+            .await?;
+        }
         FullEvent::GuildAuditLogEntryCreate { entry, guild_id } => {
+            voice::voice_moderation_audit(ctx, &data, entry, *guild_id).await?;
+            messages::anti_delete_audit(&data, entry, *guild_id);
+            let vcstatus_guild = {
+                let config = data.config.read();
+                config
+                    .vcstatus
+                    .guilds
+                    .as_ref()
+                    .is_some_and(|g| g.contains(guild_id))
+            };
+            if vcstatus_guild {
+                vc_status::vc_status_audit(ctx, &data, entry).await?;
+            }
             guilds::guild_audit_log_entry_create(ctx, entry, guild_id).await?;
         }
         FullEvent::ChannelCreate { channel } => {
@@ -98,16 +159,18 @@ pub async fn event_handler(
             id,
             guild_id,
         } => {
+            name_filter::check_vc_status(ctx, &data, *guild_id, *id, status.as_deref()).await?;
+
             let guilds = { data.config.read().vcstatus.guilds.clone() };
             if let Some(guilds) = guilds {
                 if guilds.contains(guild_id) {
-                    channels::voice_channel_status_update(ctx, old, status, id, guild_id, data)
+                    vc_status::voice_channel_status_update(ctx, old, status, id, guild_id, data)
                         .await?;
                 }
             }
         }
         FullEvent::VoiceStateUpdate { old, new } => {
-            voice::voice_state_update(ctx, old, new).await?;
+            voice::voice_state_update(ctx, old, new, data).await?;
         }
         FullEvent::GuildMemberUpdate {
             old_if_available,
@@ -116,16 +179,14 @@ pub async fn event_handler(
         } => {
             users::guild_member_update(ctx, old_if_available, new, event, data).await?;
         }
+        FullEvent::PresenceUpdate { old_data, new_data } => {
+            name_filter::check_presence(ctx, &data, old_data.as_ref(), new_data).await?;
+        }
         FullEvent::Ready { data_about_bot } => {
             misc::ready(ctx, data_about_bot, data).await?;
         }
         FullEvent::GuildMembersChunk { chunk } => {
-            println!(
-                "Chunk recieved containing {} members: {}/{}",
-                chunk.members.len(),
-                chunk.chunk_index + 1,
-                chunk.chunk_count
-            );
+            guilds::guild_members_chunk(ctx, chunk, data).await?;
         }
         FullEvent::InteractionCreate { interaction } => {
             if let Some(component) = interaction.as_message_component() {
@@ -0,0 +1,165 @@
+//! Actions shared by the automatic moderation features.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{Data, Error};
+use moth_config::{Events, ModerationAction};
+use moth_data::database::MessageAction;
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateMessage, EditMember, GuildId, Message, RoleId, Timestamp,
+    UserId,
+};
+
+/// Deletes a message, returning if it was deleted.
+pub async fn delete_message(ctx: &serenity::Context, msg: &Message, reason: &str) -> bool {
+    match msg.delete(&ctx.http, Some(reason)).await {
+        Ok(()) => true,
+        Err(e) => {
+            println!("Failed to delete message {}: {e}", msg.id);
+            false
+        }
+    }
+}
+
+/// Times out a member, returning if they were timed out.
+pub async fn timeout_member(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    duration: Duration,
+    reason: &str,
+) -> bool {
+    let until = chrono::Utc::now().timestamp() + duration.as_secs() as i64;
+    let Ok(until) = Timestamp::from_unix_timestamp(until) else {
+        return false;
+    };
+
+    let builder = EditMember::new()
+        .disable_communication_until(until)
+        .audit_log_reason(reason);
+
+    match guild_id.edit_member(&ctx.http, user_id, builder).await {
+        Ok(_) => true,
+        Err(e) => {
+            println!("Failed to time out {user_id}: {e}");
+            false
+        }
+    }
+}
+
+/// Sends a user a direct message, returning if it could be sent.
+pub async fn warn_user(ctx: &serenity::Context, user_id: UserId, content: String) -> bool {
+    match user_id
+        .dm(&ctx.http, CreateMessage::new().content(content))
+        .await
+    {
+        Ok(_) => true,
+        Err(e) => {
+            println!("Failed to warn {user_id}: {e}");
+            false
+        }
+    }
+}
+
+/// What was done to a message.
+pub struct TakenAction {
+    pub action: MessageAction,
+    /// If every part of the action went through.
+    pub succeeded: bool,
+    /// A short description of what was done.
+    pub description: String,
+}
+
+/// Applies an action to the message, returning what was done.
+pub async fn apply_message_action(
+    ctx: &serenity::Context,
+    msg: &Message,
+    action: ModerationAction,
+    timeout: Duration,
+    reason: &str,
+) -> TakenAction {
+    let (message_action, succeeded, description) = match action {
+        ModerationAction::Log => (MessageAction::Log, true, String::from("Logged")),
+        ModerationAction::Delete => {
+            if delete_message(ctx, msg, reason).await {
+                (MessageAction::Delete, true, String::from("Deleted"))
+            } else {
+                (
+                    MessageAction::Delete,
+                    false,
+                    String::from("Failed to delete"),
+                )
+            }
+        }
+        ModerationAction::Warn => {
+            let deleted = delete_message(ctx, msg, reason).await;
+            let warning = format!(
+                "Your message in <#{}> was removed: {reason}.",
+                msg.channel_id
+            );
+            let warned = warn_user(ctx, msg.author.id, warning).await;
+
+            let description = match (deleted, warned) {
+                (true, true) => String::from("Deleted and warned"),
+                (true, false) => String::from("Deleted, could not warn"),
+                (false, true) => String::from("Warned, failed to delete"),
+                (false, false) => String::from("Failed to delete and warn"),
+            };
+            (MessageAction::Warn, deleted && warned, description)
+        }
+        ModerationAction::DeleteAndTimeout => {
+            let deleted = delete_message(ctx, msg, reason).await;
+            let timed_out = match msg.guild_id {
+                Some(guild_id) => {
+                    timeout_member(ctx, guild_id, msg.author.id, timeout, reason).await
+                }
+                None => false,
+            };
+
+            let description = match (deleted, timed_out) {
+                (true, true) => format!("Deleted and timed out for {}s", timeout.as_secs()),
+                (true, false) => String::from("Deleted, failed to time out"),
+                (false, true) => format!("Timed out for {}s, failed to delete", timeout.as_secs()),
+                (false, false) => String::from("Failed to delete and time out"),
+            };
+            (
+                MessageAction::DeleteAndTimeout,
+                deleted && timed_out,
+                description,
+            )
+        }
+    };
+
+    TakenAction {
+        action: message_action,
+        succeeded,
+        description,
+    }
+}
+
+/// Sends a message to a log channel, printing instead of failing if it can't be sent.
+pub async fn log_to(ctx: &serenity::Context, channel_id: ChannelId, builder: CreateMessage<'_>) {
+    if let Err(e) = channel_id.send_message(&ctx.http, builder).await {
+        println!("Failed to send to log channel {channel_id}: {e}");
+    }
+}
+
+/// Looks up the configuration of a guild in one of the per guild configuration maps.
+pub fn guild_config<T: Clone>(
+    data: &Data,
+    guild_id: GuildId,
+    configs: impl Fn(&Events) -> Option<&HashMap<GuildId, T>>,
+) -> Option<T> {
+    configs(&data.config.read().events)
+        .and_then(|g| g.get(&guild_id))
+        .cloned()
+}
+
+/// Checks if the author of a message has any of the given roles.
+#[must_use]
+pub fn has_any_role(msg: &Message, roles: &[RoleId]) -> bool {
+    msg.member
+        .as_ref()
+        .is_some_and(|m| m.roles.iter().any(|r| roles.contains(r)))
+}
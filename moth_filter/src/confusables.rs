@@ -0,0 +1,17 @@
+use unicode_security::MixedScript;
+use unicode_security::skeleton as unicode_skeleton;
+
+/// Reduces a string to its confusable skeleton so lookalike names compare equal.
+///
+/// `ꓲ`, `l` and `1` all map to the same character, and the result is lowercased so
+/// casing does not matter either.
+#[must_use]
+pub fn skeleton(input: &str) -> String {
+    unicode_skeleton(input).collect::<String>().to_lowercase()
+}
+
+/// Checks if a string mixes scripts, such as latin letters with cyrillic lookalikes.
+#[must_use]
+pub fn is_mixed_script(input: &str) -> bool {
+    !input.is_single_script()
+}
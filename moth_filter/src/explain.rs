@@ -0,0 +1,93 @@
+//! Breaks down why the filter did or didn't flag something.
+
+use rustrict::{Censor, Type};
+
+use crate::normalise::{Token, WordLists, tokenise};
+use crate::{Severity, get_threshold, get_trie, trie_overrides};
+
+/// How a single token went through the filter.
+pub struct TokenExplanation<'a> {
+    pub token: Token<'a>,
+    /// The badlist entry the token matched.
+    pub badlist: Option<String>,
+    /// The fixlist entry that stopped the badlist match.
+    pub fixlist: Option<String>,
+    /// What rustrict rates the token as on its own.
+    pub rustrict: Type,
+    /// The trie override matching a word of the token.
+    pub trie_override: Option<(&'static str, Type)>,
+}
+
+impl TokenExplanation<'_> {
+    /// If the token was flagged by anything.
+    pub fn flagged(&self) -> bool {
+        (self.badlist.is_some() && self.fixlist.is_none()) || Severity::of(self.rustrict).is_some()
+    }
+}
+
+pub struct Explanation<'a> {
+    pub tokens: Vec<TokenExplanation<'a>>,
+    /// What rustrict rated the content as as a whole.
+    pub kind: Type,
+}
+
+impl Explanation<'_> {
+    pub fn severity(&self) -> Option<Severity> {
+        Severity::of(self.kind)
+    }
+}
+
+fn analyze_text(text: &str) -> Type {
+    let mut censor = Censor::from_str(text);
+    let censor = censor
+        .with_trie(get_trie())
+        .with_censor_threshold(get_threshold());
+
+    censor.analyze()
+}
+
+/// Runs content through every step of the filter, keeping what each token matched.
+pub fn explain<'a>(content: &'a str, lists: &WordLists) -> Explanation<'a> {
+    let tokens = tokenise(content);
+
+    let plain = tokens
+        .iter()
+        .filter(|t| !t.plain.is_empty())
+        .map(|t| t.plain.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let kind = analyze_text(&plain);
+
+    let tokens = tokens
+        .into_iter()
+        .map(|token| {
+            let badlist = lists.badlist_match(&token).map(str::to_owned);
+            let fixlist = badlist
+                .as_ref()
+                .and_then(|_| lists.fixlist_match(&token).map(str::to_owned));
+
+            let rustrict = if token.plain.is_empty() {
+                Type::NONE
+            } else {
+                analyze_text(&token.plain)
+            };
+
+            // whole words only, so `pp` isn't reported for `happy`.
+            let lowercase = token.plain.to_lowercase();
+            let trie_override = trie_overrides()
+                .iter()
+                .find(|(word, _)| lowercase.split_whitespace().any(|w| w == word))
+                .map(|(word, kind)| (word.as_str(), *kind));
+
+            TokenExplanation {
+                token,
+                badlist,
+                fixlist,
+                rustrict,
+                trie_override,
+            }
+        })
+        .collect();
+
+    Explanation { tokens, kind }
+}
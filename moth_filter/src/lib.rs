@@ -1,12 +1,23 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 use std::sync::{LazyLock, OnceLock};
-use std::{borrow::Cow, collections::HashSet};
 
 use regex::Regex;
-use rustrict::{Censor, Trie, Type};
+pub use rustrict::Type;
+use rustrict::{Censor, Trie};
 
 use moth_ansi::{BOLD, RED, RESET};
 
+mod confusables;
+pub use confusables::{is_mixed_script, skeleton};
+mod explain;
+pub use explain::{Explanation, TokenExplanation, explain};
+mod normalise;
+pub use normalise::{
+    Token, WordLists, contains_stretched, decode_leet, is_invisible, normalise_word,
+    strip_invisible, tokenise,
+};
+
 pub static WHITESPACE: std::sync::LazyLock<regex::Regex> =
     std::sync::LazyLock::new(|| regex::Regex::new(r"(\s*)(\S+)").unwrap());
 
@@ -23,14 +34,67 @@ fn get_threshold() -> Type {
         & (Type::MODERATE | Type::SEVERE)
 }
 
+const OVERRIDES_PATH: &str = "config/lists/trie_overrides.txt";
+/// Used when the overrides file can't be read, such as when running tests.
+const DEFAULT_OVERRIDES: &str = include_str!("../../config/lists/trie_overrides.txt");
+
+fn parse_override(line: &str) -> Option<(String, Type)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut parts = line.split_whitespace();
+    let word = parts.next()?.to_lowercase();
+
+    let mut categories = Type::NONE;
+    let mut severities = Type::NONE;
+    for part in parts {
+        match part.to_lowercase().as_str() {
+            "safe" => return Some((word, Type::SAFE)),
+            "profane" => categories = categories | Type::PROFANE,
+            "offensive" => categories = categories | Type::OFFENSIVE,
+            "sexual" => categories = categories | Type::SEXUAL,
+            "mean" => categories = categories | Type::MEAN,
+            "evasive" => categories = categories | Type::EVASIVE,
+            "spam" => categories = categories | Type::SPAM,
+            "mild" => severities = severities | Type::MILD,
+            "moderate" => severities = severities | Type::MODERATE,
+            "severe" => severities = severities | Type::SEVERE,
+            other => {
+                println!("Unknown type `{other}` for the trie override of `{word}`");
+                return None;
+            }
+        }
+    }
+
+    let kind = match (categories == Type::NONE, severities == Type::NONE) {
+        (true, _) => Type::SAFE,
+        (false, true) => categories,
+        (false, false) => categories & severities,
+    };
+
+    Some((word, kind))
+}
+
+/// Words whose rustrict type is overridden, loaded from the overrides file.
+pub fn trie_overrides() -> &'static [(String, Type)] {
+    static OVERRIDES: OnceLock<Vec<(String, Type)>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| {
+        let content = std::fs::read_to_string(OVERRIDES_PATH)
+            .unwrap_or_else(|_| DEFAULT_OVERRIDES.to_owned());
+
+        content.lines().filter_map(parse_override).collect()
+    })
+}
+
 fn get_trie() -> &'static Trie {
     static TRIE: OnceLock<Trie> = OnceLock::new();
     TRIE.get_or_init(|| {
         let mut trie = Trie::default();
-        // patch fix
-        trie.set("fcing", Type::SAFE);
-        trie.set("pp", Type::SAFE);
-        trie.set("ppcat", Type::SAFE);
+        for (word, kind) in trie_overrides() {
+            trie.set(word, *kind);
+        }
 
         trie
     })
@@ -59,8 +123,24 @@ pub fn preprocess(content: &str) -> Cow<'_, str> {
     }
 }
 
+/// Joins the plain form of every token for rustrict, skipping tokens that were emptied.
+fn rustrict_text<'a, 'b>(tokens: &'b [Token<'a>]) -> (String, Vec<&'b Token<'a>>) {
+    let tokens = tokens
+        .iter()
+        .filter(|t| !t.plain.is_empty())
+        .collect::<Vec<_>>();
+    let text = tokens
+        .iter()
+        .map(|t| t.plain.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (text, tokens)
+}
+
 pub fn analyze(content: &str) -> Type {
-    let processed = preprocess(content);
+    let tokens = tokenise(content);
+    let (processed, _) = rustrict_text(&tokens);
     let mut censor = Censor::from_str(&processed);
     let censor = censor
         .with_trie(get_trie())
@@ -69,51 +149,97 @@ pub fn analyze(content: &str) -> Type {
     censor.analyze()
 }
 
-pub fn filter_content<'a>(
-    content: &'a str,
-    badlist: &HashSet<String>,
-    fixlist: &HashSet<String>,
-) -> Cow<'a, str> {
-    let mut changed_words = Vec::new();
-    let processed = preprocess(content);
+/// The result of running content through the filter.
+pub struct Scan<'a> {
+    /// What rustrict rated the content as.
+    pub kind: Type,
+    /// Words that contain a badlisted word and no fixlisted word.
+    pub badlist_words: Vec<&'a str>,
+    /// Every word that was flagged, by either the badlist or rustrict.
+    pub flagged_words: Vec<&'a str>,
+}
+
+/// How bad rustrict rated some content as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Mild,
+    Moderate,
+    Severe,
+}
+
+impl Severity {
+    /// The severity of a rustrict type, if it is inappropriate at all.
+    pub fn of(kind: Type) -> Option<Severity> {
+        if kind.is(Type::INAPPROPRIATE & Type::SEVERE) {
+            Some(Severity::Severe)
+        } else if kind.is(Type::INAPPROPRIATE & Type::MODERATE) {
+            Some(Severity::Moderate)
+        } else if kind.is(Type::INAPPROPRIATE & Type::MILD) {
+            Some(Severity::Mild)
+        } else {
+            None
+        }
+    }
+}
+
+impl Scan<'_> {
+    /// The severity of the content, if rustrict found it inappropriate at all.
+    pub fn severity(&self) -> Option<Severity> {
+        Severity::of(self.kind)
+    }
+}
+
+pub fn scan<'a>(content: &'a str, lists: &WordLists) -> Scan<'a> {
+    let tokens = tokenise(content);
+
+    let mut badlist_words = Vec::new();
+    for token in &tokens {
+        let is_blacklisted =
+            lists.badlist_match(token).is_some() && lists.fixlist_match(token).is_none();
+        if is_blacklisted {
+            badlist_words.extend(&token.words);
+        }
+    }
+
+    let mut changed_words = badlist_words.clone();
+
+    let (processed, rustrict_tokens) = rustrict_text(&tokens);
     let mut censor = Censor::from_str(&processed);
     let censor = censor
         .with_trie(get_trie())
         .with_censor_threshold(get_threshold());
+    let kind = censor.analyze();
 
-    content.split_whitespace().for_each(|word| {
-        let is_blacklisted = badlist.iter().any(|badword| word.contains(badword))
-            && !fixlist.iter().any(|fixword| word.contains(fixword));
-        if is_blacklisted {
-            changed_words.push(word);
-        }
-    });
-
-    if censor.analyze() != Type::NONE {
+    if kind != Type::NONE {
         censor.reset(processed.chars());
         let censored = censor.censor();
 
-        let mut orig_iter = content.split_whitespace();
-        let mut mapped_iter = processed.split_whitespace();
-        let mut censored_iter = censored.split_whitespace();
-
-        loop {
-            match (orig_iter.next(), mapped_iter.next(), censored_iter.next()) {
-                (Some(w1), Some(mapped), Some(w2)) if mapped != w2 => {
-                    changed_words.push(w1);
-                }
-                (Some(w1), None, _) => changed_words.push(w1),
-                (Some(_), Some(_), None) => continue,
-                (None, None, None) => break,
-                _ => continue,
+        // censoring keeps the whitespace intact, so the words still line up with the tokens.
+        for (token, censored) in rustrict_tokens.iter().zip(censored.split_whitespace()) {
+            if token.plain != censored {
+                changed_words.extend(&token.words);
             }
         }
     }
 
-    if changed_words.is_empty() {
+    Scan {
+        kind,
+        badlist_words,
+        flagged_words: changed_words,
+    }
+}
+
+pub fn filter_content<'a>(content: &'a str, lists: &WordLists) -> Cow<'a, str> {
+    let scan = scan(content, lists);
+    highlight(content, &scan)
+}
+
+/// Colours the flagged words of a scan red.
+pub fn highlight<'a>(content: &'a str, scan: &Scan<'_>) -> Cow<'a, str> {
+    if scan.flagged_words.is_empty() {
         Cow::Borrowed(content)
     } else {
-        Cow::Owned(colour_string(content, &changed_words))
+        Cow::Owned(colour_string(content, &scan.flagged_words))
     }
 }
 
@@ -127,7 +253,12 @@ fn colour_string(content: &str, changed_words: &[&str]) -> String {
 
         result.push_str(leading_whitespace);
 
-        if changed_words.contains(&word) {
+        // compare by position so only the flagged occurrence of a word is coloured, spaced out
+        // letters would colour every matching letter otherwise.
+        if changed_words
+            .iter()
+            .any(|w| std::ptr::eq(w.as_ptr(), word.as_ptr()))
+        {
             write!(result, "{BOLD}{RED}{word}{RESET}").unwrap();
         } else {
             result.push_str(word);
@@ -0,0 +1,265 @@
+//! Undoes the usual ways of slipping words past the filter.
+//!
+//! Content is split into tokens that remember the original words they came from, so anything
+//! matched on the normalised form can still be highlighted in the original text.
+
+use std::collections::HashSet;
+
+use crate::{confusables::skeleton, preprocess};
+
+/// A word, or a run of spaced out single letters, from the original content.
+pub struct Token<'a> {
+    /// The original words the token was made from.
+    pub words: Vec<&'a str>,
+    /// The words without invisible characters.
+    pub visible: String,
+    /// The visible words without emotes, mentions and links.
+    ///
+    /// This is what rustrict sees, as it already handles confusables, leetspeak and repeats.
+    pub plain: String,
+    /// The fully normalised form that the word lists are compared against.
+    pub normalised: String,
+}
+
+/// Runs of single letters shorter than this are left alone, so sentences like "I am a" aren't
+/// joined.
+const MIN_LETTER_RUN: usize = 3;
+
+/// Characters that render as nothing, or as blank space that isn't treated as whitespace.
+#[must_use]
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{17B4}'
+            | '\u{17B5}'
+            | '\u{180B}'..='\u{180F}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{206F}'
+            | '\u{3164}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+            | '\u{E0000}'..='\u{E007F}'
+    )
+}
+
+#[must_use]
+pub fn strip_invisible(word: &str) -> String {
+    word.chars().filter(|c| !is_invisible(*c)).collect()
+}
+
+/// Maps the digits and symbols commonly used in place of letters back to the letter.
+#[must_use]
+pub fn decode_leet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' | '+' => 't',
+        _ => c,
+    }
+}
+
+/// Normalises a single word so lookalike spellings of it compare equal.
+///
+/// Trailing punctuation is dropped first so `!` ending a sentence isn't read as an `i`, then
+/// leetspeak is decoded, the word is reduced to its confusable skeleton and anything that isn't
+/// a letter or digit is removed.
+///
+/// Leetspeak is only decoded in words with at least one letter, so numbers and times such as
+/// `17:17` aren't read as words.
+#[must_use]
+pub fn normalise_word(word: &str) -> String {
+    let word = strip_invisible(word);
+    let word = word.trim_end_matches(['.', ',', '!', '?', ';', ':', '"', '\'', ')']);
+    let has_letter = word.chars().any(char::is_alphabetic);
+    let decoded = word
+        .chars()
+        .map(|c| if has_letter { decode_leet(c) } else { c })
+        .flat_map(char::to_lowercase)
+        .collect::<String>();
+
+    skeleton(&decoded)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Splits content into tokens, joining runs of spaced out single letters into one token.
+#[must_use]
+pub fn tokenise(content: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut letters = Vec::new();
+
+    for word in content.split_whitespace() {
+        let visible = strip_invisible(word);
+        let plain = preprocess(&visible).into_owned();
+        let normalised = normalise_word(&visible);
+
+        let token = Token {
+            words: vec![word],
+            visible,
+            plain,
+            normalised,
+        };
+
+        if token.normalised.chars().count() == 1 {
+            letters.push(token);
+            continue;
+        }
+
+        push_letters(&mut tokens, &mut letters);
+        tokens.push(token);
+    }
+
+    push_letters(&mut tokens, &mut letters);
+    tokens
+}
+
+fn push_letters<'a>(tokens: &mut Vec<Token<'a>>, letters: &mut Vec<Token<'a>>) {
+    if letters.len() < MIN_LETTER_RUN {
+        tokens.append(letters);
+        return;
+    }
+
+    let mut joined = Token {
+        words: Vec::with_capacity(letters.len()),
+        visible: String::new(),
+        plain: String::new(),
+        normalised: String::new(),
+    };
+
+    for letter in letters.drain(..) {
+        joined.words.extend(letter.words);
+        joined.visible.push_str(&letter.visible);
+        joined.plain.push_str(&letter.plain);
+    }
+
+    // normalised as a whole, so leetspeak is decoded if any of the letters is a real letter.
+    joined.normalised = normalise_word(&joined.visible);
+    tokens.push(joined);
+}
+
+/// If normalising would remove part of a list entry, such as the bars of `n||`.
+///
+/// These are matched as written, as they would otherwise shrink to a few letters found in
+/// countless words.
+fn is_literal(entry: &str) -> bool {
+    entry
+        .chars()
+        .any(|c| !c.is_alphanumeric() && decode_leet(c) == c)
+}
+
+/// A word list entry, prepared for matching.
+#[derive(Clone, Debug)]
+struct ListWord {
+    /// The entry as written in the list.
+    word: String,
+    /// What tokens are compared against, normalised unless the entry is literal.
+    pattern: String,
+    literal: bool,
+}
+
+impl ListWord {
+    fn new(word: &str) -> Option<Self> {
+        let literal = is_literal(word);
+        let pattern = if literal {
+            word.to_owned()
+        } else {
+            normalise_word(word)
+        };
+
+        (!pattern.is_empty()).then(|| ListWord {
+            word: word.to_owned(),
+            pattern,
+            literal,
+        })
+    }
+}
+
+/// The badlist and fixlist, normalised once so content can be checked against them quickly.
+#[derive(Clone, Debug, Default)]
+pub struct WordLists {
+    badlist: Vec<ListWord>,
+    fixlist: Vec<ListWord>,
+}
+
+impl WordLists {
+    #[must_use]
+    pub fn new(badlist: &HashSet<String>, fixlist: &HashSet<String>) -> Self {
+        let prepare =
+            |list: &HashSet<String>| list.iter().filter_map(|w| ListWord::new(w)).collect();
+
+        WordLists {
+            badlist: prepare(badlist),
+            fixlist: prepare(fixlist),
+        }
+    }
+
+    /// The badlist entry a token contains, if any.
+    #[must_use]
+    pub fn badlist_match(&self, token: &Token<'_>) -> Option<&str> {
+        find_match(&self.badlist, token)
+    }
+
+    /// The fixlist entry a token contains, if any.
+    #[must_use]
+    pub fn fixlist_match(&self, token: &Token<'_>) -> Option<&str> {
+        find_match(&self.fixlist, token)
+    }
+}
+
+fn find_match<'a>(list: &'a [ListWord], token: &Token<'_>) -> Option<&'a str> {
+    let visible = token.visible.to_lowercase();
+
+    list.iter()
+        .find(|entry| {
+            if entry.literal {
+                visible.contains(&entry.pattern)
+            } else {
+                contains_stretched(&token.normalised, &entry.pattern)
+            }
+        })
+        .map(|entry| entry.word.as_str())
+}
+
+/// Collapses a string into runs of the same character and how long each run is.
+fn runs(s: &str) -> Vec<(char, usize)> {
+    let mut runs: Vec<(char, usize)> = Vec::new();
+    for c in s.chars() {
+        match runs.last_mut() {
+            Some((last, count)) if *last == c => *count += 1,
+            _ => runs.push((c, 1)),
+        }
+    }
+    runs
+}
+
+/// Checks if `haystack` contains `needle`, allowing any letter of the needle to be repeated.
+///
+/// Collapsing repeats on both sides would make `ass` match `was`, so instead every run of a
+/// letter in the needle has to be matched by a run at least as long.
+#[must_use]
+pub fn contains_stretched(haystack: &str, needle: &str) -> bool {
+    let needle = runs(needle);
+    let haystack = runs(haystack);
+
+    if needle.is_empty() {
+        return false;
+    }
+
+    haystack.windows(needle.len()).any(|window| {
+        window
+            .iter()
+            .zip(&needle)
+            .all(|((h, h_count), (n, n_count))| h == n && h_count >= n_count)
+    })
+}
@@ -0,0 +1,126 @@
+//! Runs the filter over `corpus.txt`, so false positives that were fixed stay fixed.
+
+use std::collections::HashSet;
+
+use moth_ansi::{BOLD, RED, RESET};
+use moth_filter::WordLists;
+
+const CORPUS: &str = include_str!("corpus.txt");
+const BADWORDS: &str = include_str!("../../config/lists/badwords.txt");
+const FIXWORDS: &str = include_str!("../../config/lists/fixwords.txt");
+
+/// The global lists, so the corpus catches false positives of the words actually in use.
+fn lists() -> WordLists {
+    let parse = |content: &str| -> HashSet<String> {
+        content
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+    WordLists::new(&parse(BADWORDS), &parse(FIXWORDS))
+}
+
+/// Decodes `\u{...}` escapes so invisible and lookalike characters can be written plainly.
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("\\u{") {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}').expect("unterminated escape") + start;
+        let code = u32::from_str_radix(&rest[start + 3..end], 16).expect("invalid escape");
+        result.push(char::from_u32(code).expect("invalid character"));
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[test]
+fn corpus() {
+    let lists = lists();
+    let mut failures = Vec::new();
+
+    for (index, line) in CORPUS.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (expected, text) = line
+            .split_once(" | ")
+            .unwrap_or_else(|| panic!("line {} is missing ` | `", index + 1));
+        let text = unescape(text);
+
+        let scan = moth_filter::scan(&text, &lists);
+        let flagged = !scan.badlist_words.is_empty() || scan.severity().is_some();
+
+        let expected_flag = match expected {
+            "clean" => false,
+            "flag" => true,
+            other => panic!("line {} has an unknown expectation `{other}`", index + 1),
+        };
+
+        if flagged != expected_flag {
+            failures.push(format!(
+                "line {}: expected `{text}` to be {expected}, got {:?} with badlist words {:?}",
+                index + 1,
+                scan.kind,
+                scan.badlist_words
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn spaced_letters_map_back_to_the_original_words() {
+    let lists = lists();
+    let content = "a boob is not b o o b";
+
+    let scan = moth_filter::scan(content, &lists);
+    assert_eq!(scan.badlist_words, ["boob", "b", "o", "o", "b"]);
+
+    // the flagged words are highlighted where they are, `not` is left alone despite its `o`.
+    let highlighted = moth_filter::highlight(content, &scan);
+    let red = |word: &str| format!("{BOLD}{RED}{word}{RESET}");
+    let expected = format!(
+        "a {} is not {} {} {} {}",
+        red("boob"),
+        red("b"),
+        red("o"),
+        red("o"),
+        red("b")
+    );
+    assert_eq!(highlighted, expected);
+}
+
+#[test]
+fn trie_overrides_only_match_whole_words() {
+    let lists = lists();
+
+    let explanation = moth_filter::explain("happy pp", &lists);
+    let overrides = explanation
+        .tokens
+        .iter()
+        .map(|t| t.trie_override.map(|(word, _)| word))
+        .collect::<Vec<_>>();
+    assert_eq!(overrides, [None, Some("pp")]);
+}
+
+#[test]
+fn repeats_stretch_but_do_not_collapse() {
+    assert!(moth_filter::contains_stretched("pisss", "piss"));
+    assert!(moth_filter::contains_stretched("booooob", "boob"));
+    assert!(!moth_filter::contains_stretched("bob", "boob"));
+    assert!(!moth_filter::contains_stretched("pis", "piss"));
+}
+
+#[test]
+fn trie_overrides_are_loaded() {
+    let overrides = moth_filter::trie_overrides();
+    assert!(overrides.iter().any(|(word, _)| word == "ppcat"));
+}
@@ -5,7 +5,15 @@ use std::sync::{atomic::AtomicBool, Arc};
 pub async fn setup() -> Arc<Data> {
     let handler = moth_data::database::init_data().await;
 
-    let config = moth_config::MothConfig::load_config();
+    let mut config = moth_config::MothConfig::load_config();
+    match handler.get_filter_lists().await {
+        Ok(lists) => {
+            for (guild_id, lists) in lists {
+                config.events.set_filter_lists(guild_id, lists);
+            }
+        }
+        Err(e) => println!("Failed to load the filter lists: {e}"),
+    }
     let starboard_config = starboard_config();
 
     Arc::new(Data {
@@ -15,6 +23,12 @@ pub async fn setup() -> Arc<Data> {
         reqwest: reqwest::Client::new(),
         config: parking_lot::RwLock::new(config),
         anti_delete_cache: moth_data::structs::AntiDeleteCache::default(),
+        expression_snapshots: moth_data::structs::ExpressionSnapshots::default(),
+        vc_status_correlation: moth_data::structs::VcStatusCorrelation::default(),
+        voice_moderation_correlation: moth_data::structs::VoiceModerationCorrelation::default(),
+        invite_uses: dashmap::DashMap::new(),
+        raid_cache: moth_data::structs::RaidCache::default(),
+        spam_cache: moth_data::structs::SpamCache::default(),
         starboard_config,
     })
 }